@@ -0,0 +1,240 @@
+//! Times a bulk load, and a single row edit, for lists of increasing size,
+//! comparing the WithsModel and WithsList with the path they replaced:
+//! a Vec of withs searched linearly for duplicates, backing a
+//! QStandardItemModel which is filled a row at a time, and whose every row
+//! is redecorated each time the withs change.
+//!
+//! ```text
+//! QT_QPA_PLATFORM=offscreen cargo run --release --example model_bench
+//! ```
+use pbgui_withs::{utility::qs, ListItem, WithEntry, WithsList, WithsListConfig, WithsModel};
+use qt_core::{ItemDataRole, QSortFilterProxyModel, QVariant};
+use qt_gui::{QStandardItem, QStandardItemModel};
+use qt_widgets::{
+    cpp_core::{CppBox, MutPtr},
+    QApplication, QListView, QWidget,
+};
+use std::time::{Duration, Instant};
+
+const MODEL_SIZES: [usize; 3] = [1_000, 10_000, 50_000];
+const LIST_SIZES: [usize; 2] = [1_000, 10_000];
+// the number of single row edits timed for each size
+const EDITS: u32 = 100;
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        println!("WithsModel");
+        print_header();
+        for size in MODEL_SIZES.iter() {
+            bench_model(*size);
+        }
+
+        let mut main = QWidget::new_0a();
+        let withs_list = WithsList::new(main.as_mut_ptr(), WithsListConfig::default());
+        let mut legacy = LegacyList::new();
+        println!("\nWithsList");
+        print_header();
+        for size in LIST_SIZES.iter() {
+            bench_list(*size, &withs_list, &mut legacy);
+        }
+        0
+    })
+}
+
+fn print_header() {
+    println!(
+        "{:>8} {:>14} {:>14} {:>14} {:>14}",
+        "items", "load", "load (prev)", "edit", "edit (prev)"
+    );
+}
+
+fn print_row(
+    size: usize,
+    load: Duration,
+    legacy_load: Duration,
+    edit: Duration,
+    legacy_edit: Duration,
+) {
+    println!(
+        "{:>8} {:>14?} {:>14?} {:>14?} {:>14?}",
+        size, load, legacy_load, edit, legacy_edit
+    );
+}
+
+fn items(size: usize) -> Vec<String> {
+    (0..size)
+        .map(|x| format!("package{}-{}.0", x, x % 7))
+        .collect()
+}
+
+fn bench_model(size: usize) {
+    let items = items(size);
+    let mut model = WithsModel::new();
+    let load = time(|| model.set_items(items.clone()).expect("unique items"));
+    let mut legacy = LegacyModel::default();
+    let legacy_load = time(|| legacy.set_items(&items));
+
+    // an edit at the top of the list shifts every row beneath it
+    let edit = time(|| {
+        for _ in 0..EDITS {
+            model.insert(0, "extra-1.0").expect("unique item");
+            model.remove("extra").expect("item present");
+        }
+    }) / EDITS;
+    let legacy_edit = time(|| {
+        for _ in 0..EDITS {
+            legacy.insert(0, "extra-1.0");
+            legacy.remove("extra");
+        }
+    }) / EDITS;
+    print_row(size, load, legacy_load, edit, legacy_edit);
+}
+
+fn bench_list(size: usize, withs_list: &WithsList, legacy: &mut LegacyList) {
+    let items = items(size);
+    let load = time(|| withs_list.set_items(items.clone()).expect("unique items"));
+    let legacy_load = time(|| legacy.set_items(&items));
+
+    let edit = time(|| {
+        for _ in 0..EDITS {
+            withs_list.add_item("extra-1.0").expect("unique item");
+            withs_list.remove_items(&["extra"]).expect("item present");
+        }
+    }) / EDITS;
+    let legacy_edit = time(|| {
+        for _ in 0..EDITS {
+            let row = legacy.model.items.len();
+            legacy.insert(row, "extra-1.0");
+            legacy.remove("extra");
+        }
+    }) / EDITS;
+    print_row(size, load, legacy_load, edit, legacy_edit);
+}
+
+fn time<F: FnOnce()>(func: F) -> Duration {
+    let start = Instant::now();
+    func();
+    start.elapsed()
+}
+
+// The model as it was prior to the package index, searching its withs
+// linearly for duplicates and lookups
+#[derive(Default)]
+struct LegacyModel {
+    items: Vec<WithEntry>,
+}
+
+impl LegacyModel {
+    fn set_items(&mut self, items: &[String]) {
+        self.items.clear();
+        for item in items {
+            let row = self.items.len();
+            self.insert(row, item);
+        }
+    }
+
+    fn insert(&mut self, row: usize, item: &str) -> usize {
+        let entry = WithEntry::parse(item).expect("valid item");
+        assert!(!self.items.iter().any(|x| x.name == entry.name));
+        self.items.insert(row, entry);
+        row
+    }
+
+    fn remove(&mut self, item: &str) -> usize {
+        let row = self
+            .items
+            .iter()
+            .position(|x| x.name == item)
+            .expect("item present");
+        self.items.remove(row);
+        row
+    }
+}
+
+// The list as it was prior to the package index, appending its rows one
+// at a time, and redecorating every row whenever the withs change
+struct LegacyList {
+    model: LegacyModel,
+    qt_model: CppBox<QStandardItemModel>,
+    _proxy: CppBox<QSortFilterProxyModel>,
+    _view: CppBox<QListView>,
+}
+
+impl LegacyList {
+    fn new() -> Self {
+        unsafe {
+            let mut qt_model = QStandardItemModel::new_0a();
+            let mut proxy = QSortFilterProxyModel::new_0a();
+            proxy.set_source_model(qt_model.as_mut_ptr().static_upcast_mut());
+            let mut view = QListView::new_0a();
+            view.set_model(proxy.as_mut_ptr().static_upcast_mut());
+            Self {
+                model: LegacyModel::default(),
+                qt_model,
+                _proxy: proxy,
+                _view: view,
+            }
+        }
+    }
+
+    fn set_items(&mut self, items: &[String]) {
+        self.model.set_items(items);
+        unsafe {
+            let cnt = self.qt_model.row_count_0a();
+            self.qt_model.remove_rows_2a(0, cnt);
+            for entry in &self.model.items {
+                self.qt_model
+                    .append_row_q_standard_item(Self::new_item(entry).into_ptr());
+            }
+        }
+        self.redecorate();
+    }
+
+    fn insert(&mut self, row: usize, item: &str) {
+        let row = self.model.insert(row, item);
+        let item = Self::new_item(&self.model.items[row]);
+        unsafe {
+            self.qt_model
+                .insert_row_int_q_standard_item(row as i32, item.into_ptr());
+        }
+        self.redecorate();
+    }
+
+    fn remove(&mut self, item: &str) {
+        let row = self.model.remove(item);
+        unsafe {
+            self.qt_model.remove_row_1a(row as i32);
+        }
+        self.redecorate();
+    }
+
+    fn redecorate(&mut self) {
+        unsafe {
+            for (row, entry) in self.model.items.iter().enumerate() {
+                let mut si: MutPtr<QStandardItem> = self.qt_model.item_1a(row as i32);
+                let mut font = si.font();
+                font.set_italic(false);
+                si.set_font(&font);
+                si.set_tool_tip(&qs(entry.tooltip()));
+                si.set_data_2a(
+                    &QVariant::from_q_string(&qs("")),
+                    ItemDataRole::UserRole.to_int() + 1,
+                );
+            }
+        }
+    }
+
+    fn new_item(entry: &WithEntry) -> CppBox<QStandardItem> {
+        unsafe {
+            let mut si = QStandardItem::new();
+            si.set_text(&qs(entry.display_text()));
+            si.set_tool_tip(&qs(entry.tooltip()));
+            si.set_data_2a(
+                &QVariant::from_q_string(&qs(entry.to_string())),
+                ItemDataRole::UserRole.to_int(),
+            );
+            si.set_checkable(true);
+            si
+        }
+    }
+}
@@ -83,9 +83,27 @@ fn main() {
             "wambase",
             "xerces",
         ]);
+        withs_list
+            .borrow()
+            .set_package_versions("houdini", vec!["17.5.460", "18.0.348", "18.0.416"]);
+        withs_list
+            .borrow()
+            .set_package_versions("maya", vec!["2019", "2020"]);
         // find_shortcut.activated().connect(&find_slot);
         // add_shortcut.activated().connect(&add_slot);
 
+        withs_list
+            .borrow()
+            .on_item_added(|item, row| println!("added {} at row {}", item, row));
+        withs_list
+            .borrow()
+            .on_item_removed(|item, row| println!("removed {} from row {}", item, row));
+
+        withs_list.borrow().on_save(|items| {
+            println!("saving {}", items.join(" "));
+            true
+        });
+
         withs_list.borrow_mut().set_add_mode();
         withs_list.borrow_mut().set_cb_max_visible_items(50);
         let mut print_button = QPushButton::from_q_string(&qs("pushme"));
@@ -0,0 +1,614 @@
+//! Key bindings of the WithsList's actions.
+//!
+//! Each Action is bound to any number of KeySequences, written in Qt's
+//! portable text format (eg `Ctrl+Shift+F`, or `Ctrl+K, Ctrl+C` for a
+//! sequence of chords). Sequences are validated as they are bound, and a
+//! sequence may trigger only one action, so binding a sequence which
+//! collides with, or is a prefix of, another action's sequence is an error.
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// the maximum number of chords in a sequence, as supported by qt
+const MAX_CHORDS: usize = 4;
+
+/// The actions of the WithsList which may be bound to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    /// Add, find or filter by the combobox's text, as per the mode
+    Enter,
+    /// Remove the selected items
+    Delete,
+    /// Copy the selected items to the clipboard and remove them
+    Cut,
+    /// Copy the selected items to the clipboard
+    Copy,
+    /// Add the items held by the clipboard
+    Paste,
+    /// Undo the last edit
+    Undo,
+    /// Redo the last undone edit
+    Redo,
+    /// Switch to find mode
+    FindMode,
+    /// Switch to filter mode
+    FilterMode,
+    /// Switch to add mode
+    AddMode,
+    /// Move the selected items up a row
+    MoveUp,
+    /// Move the selected items down a row
+    MoveDown,
+    /// Move the selected items to the top
+    MoveToTop,
+    /// Move the selected items to the bottom
+    MoveToBottom,
+    /// Disable the selected items, or enable them if they are all disabled
+    ToggleEnabled,
+}
+
+impl Action {
+    /// All of the actions
+    pub const ALL: [Action; 15] = [
+        Action::Enter,
+        Action::Delete,
+        Action::Cut,
+        Action::Copy,
+        Action::Paste,
+        Action::Undo,
+        Action::Redo,
+        Action::FindMode,
+        Action::FilterMode,
+        Action::AddMode,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveToTop,
+        Action::MoveToBottom,
+        Action::ToggleEnabled,
+    ];
+
+    /// Retrieve the name of the action, as used in settings files
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enter => "enter",
+            Self::Delete => "delete",
+            Self::Cut => "cut",
+            Self::Copy => "copy",
+            Self::Paste => "paste",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::FindMode => "find_mode",
+            Self::FilterMode => "filter_mode",
+            Self::AddMode => "add_mode",
+            Self::MoveUp => "move_up",
+            Self::MoveDown => "move_down",
+            Self::MoveToTop => "move_to_top",
+            Self::MoveToBottom => "move_to_bottom",
+            Self::ToggleEnabled => "toggle_enabled",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Action {
+    type Err = BindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|action| action.as_str() == s)
+            .copied()
+            .ok_or_else(|| BindingError::UnknownAction(s.to_string()))
+    }
+}
+
+/// Errors returned when binding keys to actions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingError {
+    /// The sequence could not be parsed. Holds the sequence and the reason
+    InvalidSequence { sequence: String, reason: String },
+    /// The action's name is not recognized
+    UnknownAction(String),
+    /// The sequence is bound to, or is a prefix of a sequence bound to,
+    /// another action
+    Collision {
+        sequence: KeySequence,
+        action: Action,
+        other: Action,
+    },
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSequence { sequence, reason } => {
+                write!(f, "invalid key sequence '{}': {}", sequence, reason)
+            }
+            Self::UnknownAction(action) => write!(f, "unknown action '{}'", action),
+            Self::Collision {
+                sequence,
+                action,
+                other,
+            } => write!(
+                f,
+                "key sequence '{}' of {} collides with a key sequence of {}",
+                sequence, action, other
+            ),
+        }
+    }
+}
+
+impl Error for BindingError {}
+
+// modifiers, in the order in which qt writes them
+const MODIFIERS: [&str; 4] = ["Meta", "Ctrl", "Alt", "Shift"];
+
+// named keys, along with any aliases, in qt's portable text format
+const NAMED_KEYS: [(&str, &[&str]); 18] = [
+    ("Return", &[]),
+    ("Enter", &[]),
+    ("Backspace", &[]),
+    ("Del", &["Delete"]),
+    ("Ins", &["Insert"]),
+    ("Esc", &["Escape"]),
+    ("Tab", &[]),
+    ("Backtab", &[]),
+    ("Space", &[]),
+    ("Home", &[]),
+    ("End", &[]),
+    ("Up", &[]),
+    ("Down", &[]),
+    ("Left", &[]),
+    ("Right", &[]),
+    ("PgUp", &["PageUp"]),
+    ("PgDown", &["PageDown"]),
+    ("Menu", &[]),
+];
+
+/// A single chord of a KeySequence, being a key and its modifiers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Chord {
+    // flags indexing MODIFIERS
+    modifiers: [bool; 4],
+    key: String,
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the key itself may be '+', as in Ctrl++
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => match s.rfind('+') {
+                Some(idx) => (&s[..idx], &s[idx + 1..]),
+                None => ("", s),
+            },
+        };
+        let mut flags = [false; 4];
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            let idx = MODIFIERS
+                .iter()
+                .position(|m| m.eq_ignore_ascii_case(modifier.trim()))
+                .ok_or_else(|| format!("unknown modifier '{}'", modifier.trim()))?;
+            flags[idx] = true;
+        }
+        Ok(Self {
+            modifiers: flags,
+            key: canonical_key(key.trim())?,
+        })
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, set) in MODIFIERS.iter().zip(&self.modifiers) {
+            if *set {
+                write!(f, "{}+", modifier)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+// the name of a key in qt's portable text format
+fn canonical_key(key: &str) -> Result<String, String> {
+    if key.is_empty() {
+        return Err("missing key".to_string());
+    }
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_whitespace() || c.is_control() {
+            return Err(format!("unknown key '{}'", key));
+        }
+        return Ok(c.to_uppercase().to_string());
+    }
+    if let Some((name, _)) = NAMED_KEYS.iter().find(|(name, aliases)| {
+        name.eq_ignore_ascii_case(key) || aliases.iter().any(|a| a.eq_ignore_ascii_case(key))
+    }) {
+        return Ok(name.to_string());
+    }
+    // function keys
+    if key.len() > 1 && key[..1].eq_ignore_ascii_case("f") {
+        if let Ok(number @ 1..=35) = key[1..].parse::<u8>() {
+            return Ok(format!("F{}", number));
+        }
+    }
+    Err(format!("unknown key '{}'", key))
+}
+
+/// A sequence of up to four chords (eg `Ctrl+F`, or `Ctrl+K, Ctrl+C`).
+/// Sequences are parsed ignoring case, and are displayed in Qt's portable
+/// text format, so that equivalent sequences compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence {
+    chords: Vec<Chord>,
+}
+
+impl KeySequence {
+    /// Determine whether the sequence begins with all of the chords of the
+    /// other sequence. A sequence is a prefix of itself.
+    ///
+    /// # Arguments
+    /// * `other` - The KeySequence which may be a prefix
+    ///
+    /// # Returns
+    /// * bool
+    pub fn starts_with(&self, other: &KeySequence) -> bool {
+        self.chords.starts_with(&other.chords)
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = BindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| BindingError::InvalidSequence {
+            sequence: s.to_string(),
+            reason,
+        };
+        // the key of a chord may itself be a comma, as in Ctrl+,
+        let mut chords = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|(idx, c)| *c == ',' && !rest[..*idx].ends_with('+'))
+                .map_or(rest.len(), |(idx, _)| idx);
+            chords.push(rest[..end].trim().parse::<Chord>().map_err(invalid)?);
+            rest = rest[end..].trim_start_matches(',').trim();
+        }
+        if chords.is_empty() {
+            return Err(invalid("empty sequence".to_string()));
+        }
+        if chords.len() > MAX_CHORDS {
+            return Err(invalid(format!("more than {} chords", MAX_CHORDS)));
+        }
+        Ok(Self { chords })
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, chord) in self.chords.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+/// The table of key sequences bound to each Action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: BTreeMap<Action, Vec<KeySequence>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let defaults: [(Action, &[&str]); 15] = [
+            (Action::Enter, &["Return"]),
+            (Action::Delete, &["Backspace"]),
+            (Action::Cut, &["Ctrl+X", "Shift+Del"]),
+            (Action::Copy, &["Ctrl+C", "Ctrl+Ins"]),
+            (Action::Paste, &["Ctrl+V", "Shift+Ins"]),
+            (Action::Undo, &["Ctrl+Z"]),
+            (Action::Redo, &["Ctrl+Shift+Z"]),
+            (Action::FindMode, &["Ctrl+F"]),
+            (Action::FilterMode, &["Ctrl+Shift+F"]),
+            (Action::AddMode, &["Ctrl+A"]),
+            (Action::MoveUp, &["Alt+Up"]),
+            (Action::MoveDown, &["Alt+Down"]),
+            (Action::MoveToTop, &["Alt+Home"]),
+            (Action::MoveToBottom, &["Alt+End"]),
+            (Action::ToggleEnabled, &["Ctrl+/"]),
+        ];
+        let mut bindings = Self::new();
+        for (action, sequences) in defaults.iter() {
+            bindings
+                .bind(*action, sequences)
+                .expect("default key bindings are valid");
+        }
+        bindings
+    }
+}
+
+impl KeyBindings {
+    /// New up a KeyBindings without any bindings. See also `default`
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * KeyBindings instance
+    pub fn new() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    /// Bind the sequences to the action, replacing its existing sequences.
+    /// The bindings are left untouched if any of the sequences is invalid,
+    /// or collides with a sequence of another action.
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    /// * `sequences` - The key sequences, as &str or String (eg `Ctrl+F`)
+    ///
+    /// # Returns
+    /// * Ok, or a BindingError
+    pub fn bind<S>(&mut self, action: Action, sequences: &[S]) -> Result<(), BindingError>
+    where
+        S: AsRef<str>,
+    {
+        let mut parsed = Vec::with_capacity(sequences.len());
+        for sequence in sequences {
+            let sequence = sequence.as_ref().parse::<KeySequence>()?;
+            if !parsed.contains(&sequence) {
+                parsed.push(sequence);
+            }
+        }
+        for sequence in &parsed {
+            if let Some(other) = self.colliding_action(action, sequence) {
+                return Err(BindingError::Collision {
+                    sequence: sequence.clone(),
+                    action,
+                    other,
+                });
+            }
+        }
+        self.bindings.insert(action, parsed);
+        Ok(())
+    }
+
+    /// Builder style variant of `bind`
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    /// * `sequences` - The key sequences, as &str or String (eg `Ctrl+F`)
+    ///
+    /// # Returns
+    /// * Ok wrapping the KeyBindings, or a BindingError
+    pub fn with_binding<S>(mut self, action: Action, sequences: &[S]) -> Result<Self, BindingError>
+    where
+        S: AsRef<str>,
+    {
+        self.bind(action, sequences)?;
+        Ok(self)
+    }
+
+    /// Remove the sequences bound to the action, leaving it without a
+    /// shortcut
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    ///
+    /// # Returns
+    /// * None
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    /// Retrieve the sequences bound to the action
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    ///
+    /// # Returns
+    /// * Slice of KeySequences, which is empty if the action is unbound
+    pub fn sequences(&self, action: Action) -> &[KeySequence] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Retrieve the action bound to the sequence
+    ///
+    /// # Arguments
+    /// * `sequence` - The KeySequence
+    ///
+    /// # Returns
+    /// * Some(Action), or None if the sequence is unbound
+    pub fn action(&self, sequence: &KeySequence) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, sequences)| sequences.contains(sequence))
+            .map(|(action, _)| *action)
+    }
+
+    /// Iterate over the actions, in order, along with their sequences
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Iterator of the Action and its KeySequences
+    pub fn iter(&self) -> impl Iterator<Item = (Action, &[KeySequence])> {
+        self.bindings
+            .iter()
+            .map(|(action, sequences)| (*action, sequences.as_slice()))
+    }
+
+    /// Check that no sequence collides with a sequence of another action.
+    /// Bindings made through `bind` are checked as they are made, so this
+    /// only fails for bindings assembled by other means.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok, or the first Collision
+    pub fn validate(&self) -> Result<(), BindingError> {
+        for (action, sequences) in self.iter() {
+            for sequence in sequences {
+                if let Some(other) = self.colliding_action(action, sequence) {
+                    return Err(BindingError::Collision {
+                        sequence: sequence.clone(),
+                        action,
+                        other,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the first action, other than the supplied one, with a sequence which
+    // is the same as, or a prefix of, or prefixed by, the sequence
+    fn colliding_action(&self, action: Action, sequence: &KeySequence) -> Option<Action> {
+        self.iter()
+            .filter(|(other, _)| *other != action)
+            .find(|(_, sequences)| {
+                sequences
+                    .iter()
+                    .any(|other| other.starts_with(sequence) || sequence.starts_with(other))
+            })
+            .map(|(other, _)| other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(text: &str) -> KeySequence {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn sequences_parse_to_qt_portable_text() {
+        for (text, expected) in &[
+            ("ctrl+shift+f", "Ctrl+Shift+F"),
+            ("Shift+Ctrl+F", "Ctrl+Shift+F"),
+            ("alt+pageup", "Alt+PgUp"),
+            ("Escape", "Esc"),
+            ("f12", "F12"),
+            ("Ctrl++", "Ctrl++"),
+            ("Ctrl+,", "Ctrl+,"),
+            ("Ctrl+K,ctrl+c", "Ctrl+K, Ctrl+C"),
+        ] {
+            assert_eq!(sequence(text).to_string(), *expected, "{}", text);
+        }
+        assert_eq!(sequence("ctrl+z"), sequence("Ctrl+Z"));
+    }
+
+    #[test]
+    fn invalid_sequences_are_rejected() {
+        for text in &["", "Ctrl+", "Hyper+F", "F36", "Ctrl+Tilde", "A, B, C, D, E"] {
+            assert!(
+                matches!(
+                    text.parse::<KeySequence>(),
+                    Err(BindingError::InvalidSequence { .. })
+                ),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            "jump".parse::<Action>(),
+            Err(BindingError::UnknownAction("jump".to_string()))
+        );
+    }
+
+    #[test]
+    fn colliding_sequences_are_rejected() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(
+            bindings.bind(Action::Undo, &["Ctrl+U", "ctrl+f"]),
+            Err(BindingError::Collision {
+                sequence: sequence("Ctrl+F"),
+                action: Action::Undo,
+                other: Action::FindMode,
+            })
+        );
+        // the bindings are left untouched
+        assert_eq!(bindings.sequences(Action::Undo), &[sequence("Ctrl+Z")]);
+        assert_eq!(bindings.action(&sequence("Ctrl+U")), None);
+        assert_eq!(bindings, KeyBindings::default());
+    }
+
+    #[test]
+    fn sequences_may_not_prefix_those_of_other_actions() {
+        let mut bindings = KeyBindings::new();
+        bindings.bind(Action::MoveUp, &["Ctrl+K, Ctrl+U"]).unwrap();
+        // a prefix of another action's sequence
+        assert!(matches!(
+            bindings.bind(Action::Delete, &["Ctrl+K"]),
+            Err(BindingError::Collision {
+                other: Action::MoveUp,
+                ..
+            })
+        ));
+        // prefixed by another action's sequence
+        assert!(matches!(
+            bindings.bind(Action::Delete, &["Ctrl+K, Ctrl+U, X"]),
+            Err(BindingError::Collision {
+                other: Action::MoveUp,
+                ..
+            })
+        ));
+        // an action's own sequences may share a prefix
+        bindings
+            .bind(Action::MoveUp, &["Ctrl+K", "Ctrl+K, Ctrl+U"])
+            .unwrap();
+        // sequences which merely share a first chord do not collide
+        bindings
+            .bind(Action::MoveDown, &["Ctrl+J, Ctrl+D"])
+            .unwrap();
+        bindings.bind(Action::Delete, &["Ctrl+J, Ctrl+X"]).unwrap();
+        assert_eq!(bindings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn bindings_override_the_defaults() {
+        let mut bindings = KeyBindings::default()
+            .with_binding(Action::Undo, &["Ctrl+U", "Ctrl+U"])
+            .unwrap();
+        assert_eq!(bindings.sequences(Action::Undo), &[sequence("Ctrl+U")]);
+        assert_eq!(bindings.action(&sequence("Ctrl+Z")), None);
+        assert_eq!(
+            bindings.sequences(Action::Redo),
+            &[sequence("Ctrl+Shift+Z")]
+        );
+        // a sequence is free to rebind once its action is unbound
+        assert!(bindings.bind(Action::Redo, &["Ctrl+F"]).is_err());
+        bindings.unbind(Action::FindMode);
+        bindings.bind(Action::Redo, &["Ctrl+F"]).unwrap();
+        assert_eq!(bindings.action(&sequence("Ctrl+F")), Some(Action::Redo));
+        assert!(bindings.sequences(Action::FindMode).is_empty());
+        assert_eq!(bindings.iter().count(), Action::ALL.len() - 1);
+    }
+}
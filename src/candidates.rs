@@ -0,0 +1,462 @@
+//! Sources of the package names offered by the WithsList's combobox.
+//!
+//! Rather than pushing every package name into the combobox up front via
+//! `set_cb_items`, a CandidateProvider may be installed, which the WithsList
+//! queries on demand, a page at a time, as the user types.
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+/// Error returned by a CandidateProvider
+pub type CandidateError = Box<dyn Error + Send + Sync>;
+
+/// The default number of candidates requested per page
+pub const DEFAULT_PAGE_SIZE: usize = 256;
+
+/// A request for a page of candidates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandidateQuery<'a> {
+    /// Only candidates starting with the prefix, ignoring case, are returned
+    pub prefix: &'a str,
+    /// The number of matching candidates to skip
+    pub offset: usize,
+    /// The maximum number of candidates to return
+    pub limit: usize,
+}
+
+impl<'a> CandidateQuery<'a> {
+    /// New up a CandidateQuery for the first page of candidates
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the candidates
+    /// * `limit` - The maximum number of candidates to return
+    ///
+    /// # Returns
+    /// * CandidateQuery instance
+    pub fn new(prefix: &'a str, limit: usize) -> Self {
+        Self {
+            prefix,
+            offset: 0,
+            limit,
+        }
+    }
+
+    /// Retrieve the query for the page following this one
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * CandidateQuery instance
+    pub fn next_page(&self) -> Self {
+        Self {
+            offset: self.offset + self.limit,
+            ..*self
+        }
+    }
+
+    /// Determine whether the candidate matches the query's prefix
+    ///
+    /// # Arguments
+    /// * `candidate` - The candidate to test
+    ///
+    /// # Returns
+    /// * bool
+    pub fn matches(&self, candidate: &str) -> bool {
+        candidate
+            .to_lowercase()
+            .starts_with(&self.prefix.to_lowercase())
+    }
+
+    /// Select the page of matching candidates requested by the query
+    ///
+    /// # Arguments
+    /// * `candidates` - All of the candidates, in order
+    ///
+    /// # Returns
+    /// * CandidatePage
+    pub fn paginate<I, S>(&self, candidates: I) -> CandidatePage
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let prefix = self.prefix.to_lowercase();
+        let mut candidates = candidates
+            .into_iter()
+            .filter(|candidate| candidate.as_ref().to_lowercase().starts_with(&prefix))
+            .skip(self.offset)
+            .take(self.limit.saturating_add(1))
+            .map(|candidate| candidate.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let has_more = candidates.len() > self.limit;
+        candidates.truncate(self.limit);
+        CandidatePage {
+            candidates,
+            has_more,
+        }
+    }
+}
+
+/// A page of candidates returned by a CandidateProvider
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CandidatePage {
+    /// The candidates
+    pub candidates: Vec<String>,
+    /// Whether there are further candidates following this page
+    pub has_more: bool,
+}
+
+/// Supplies the package names offered by the combobox. Providers are shared
+/// with the WithsList, and so must be Send and Sync.
+pub trait CandidateProvider: Send + Sync {
+    /// Retrieve a page of the candidates starting with the query's prefix
+    ///
+    /// # Arguments
+    /// * `query` - The prefix and page requested
+    ///
+    /// # Returns
+    /// * Ok wrapping the CandidatePage, or a CandidateError
+    fn query(&self, query: &CandidateQuery) -> Result<CandidatePage, CandidateError>;
+
+    /// Determine whether the supplied name is a candidate. The default
+    /// implementation pages through the candidates starting with the name.
+    ///
+    /// # Arguments
+    /// * `name` - The package name
+    ///
+    /// # Returns
+    /// * Ok wrapping a bool, or a CandidateError
+    fn contains(&self, name: &str) -> Result<bool, CandidateError> {
+        let mut query = CandidateQuery::new(name, DEFAULT_PAGE_SIZE);
+        loop {
+            let page = self.query(&query)?;
+            if page.candidates.iter().any(|candidate| candidate == name) {
+                return Ok(true);
+            }
+            if !page.has_more {
+                return Ok(false);
+            }
+            query = query.next_page();
+        }
+    }
+}
+
+/// Page through the candidates starting with the prefix, collecting up to
+/// `limit` of them
+///
+/// # Arguments
+/// * `provider` - The CandidateProvider
+/// * `prefix` - The prefix of the candidates
+/// * `limit` - The maximum number of candidates to collect
+///
+/// # Returns
+/// * Ok wrapping a Vector of candidates, or a CandidateError
+pub fn collect_candidates(
+    provider: &dyn CandidateProvider,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<String>, CandidateError> {
+    let mut candidates = Vec::new();
+    let mut query = CandidateQuery::new(prefix, DEFAULT_PAGE_SIZE.min(limit));
+    while candidates.len() < limit {
+        let page = provider.query(&query)?;
+        candidates.extend(page.candidates);
+        if !page.has_more {
+            break;
+        }
+        query = query.next_page();
+    }
+    candidates.truncate(limit);
+    Ok(candidates)
+}
+
+/// Provides candidates from a fixed list of package names
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StaticCandidates {
+    candidates: Vec<String>,
+}
+
+impl StaticCandidates {
+    /// New up a StaticCandidates
+    ///
+    /// # Arguments
+    /// * `candidates` - Vector of package names, as &str or String
+    ///
+    /// # Returns
+    /// * StaticCandidates instance
+    pub fn new<I>(candidates: Vec<I>) -> Self
+    where
+        I: Into<String>,
+    {
+        Self {
+            candidates: candidates.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl CandidateProvider for StaticCandidates {
+    fn query(&self, query: &CandidateQuery) -> Result<CandidatePage, CandidateError> {
+        Ok(query.paginate(&self.candidates))
+    }
+}
+
+// The listing of a directory or file, cached until the modification time
+// of the directory or file changes, so that paging through the candidates
+// does not rescan for each page. The cache takes no part in comparisons.
+#[derive(Default)]
+struct ListingCache {
+    listing: Mutex<Option<(SystemTime, Arc<Vec<String>>)>>,
+}
+
+impl ListingCache {
+    // retrieve the listing of the path, scanning it if it has been modified
+    // since it was last scanned. Paths whose modification time is not
+    // available are scanned each time.
+    fn get<F>(&self, path: &Path, scan: F) -> Result<Arc<Vec<String>>, CandidateError>
+    where
+        F: FnOnce() -> Result<Vec<String>, CandidateError>,
+    {
+        let modified = match fs::metadata(path)?.modified() {
+            Ok(modified) => modified,
+            Err(_) => return Ok(Arc::new(scan()?)),
+        };
+        let mut cached = self.listing.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((scanned, listing)) = cached.as_ref() {
+            if *scanned == modified {
+                return Ok(listing.clone());
+            }
+        }
+        let listing = Arc::new(scan()?);
+        *cached = Some((modified, listing.clone()));
+        Ok(listing)
+    }
+}
+
+impl Clone for ListingCache {
+    fn clone(&self) -> Self {
+        let cached = self.listing.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            listing: Mutex::new(cached.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for ListingCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListingCache").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ListingCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ListingCache {}
+
+/// Provides candidates from the subdirectories of a package repository,
+/// each of which is a package, in sorted order. Hidden directories are
+/// skipped. The sorted listing is cached, and the directory is rescanned
+/// only once its modification time changes, so that newly released
+/// packages are picked up without rescanning for each page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryCandidates {
+    root: PathBuf,
+    cache: ListingCache,
+}
+
+impl DirectoryCandidates {
+    /// New up a DirectoryCandidates
+    ///
+    /// # Arguments
+    /// * `root` - The directory whose subdirectories are packages
+    ///
+    /// # Returns
+    /// * DirectoryCandidates instance
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            root: root.into(),
+            cache: ListingCache::default(),
+        }
+    }
+
+    // list the names of the package directories, sorted, as the order of
+    // read_dir is unspecified and pages must be stable
+    fn scan(&self) -> Result<Vec<String>, CandidateError> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if !name.starts_with('.') {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+impl CandidateProvider for DirectoryCandidates {
+    fn query(&self, query: &CandidateQuery) -> Result<CandidatePage, CandidateError> {
+        let listing = self.cache.get(&self.root, || self.scan())?;
+        Ok(query.paginate(listing.iter()))
+    }
+}
+
+/// Provides candidates from a file listing a package name per line, in the
+/// order of the file. Blank lines and lines starting with `#` are skipped.
+/// The listing is cached, and the file is reread only once its
+/// modification time changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCandidates {
+    path: PathBuf,
+    cache: ListingCache,
+}
+
+impl FileCandidates {
+    /// New up a FileCandidates
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * FileCandidates instance
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            cache: ListingCache::default(),
+        }
+    }
+
+    // list the package names held by the file
+    fn scan(&self) -> Result<Vec<String>, CandidateError> {
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+impl CandidateProvider for FileCandidates {
+    fn query(&self, query: &CandidateQuery) -> Result<CandidatePage, CandidateError> {
+        let listing = self.cache.get(&self.path, || self.scan())?;
+        Ok(query.paginate(listing.iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // a fresh directory for the candidates of a test
+    fn candidates_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "pbgui-withs-candidates-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // page through all of the candidates starting with the prefix
+    fn pages(provider: &dyn CandidateProvider, prefix: &str, limit: usize) -> Vec<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut query = CandidateQuery::new(prefix, limit);
+        loop {
+            let page = provider.query(&query).unwrap();
+            pages.push(page.candidates);
+            if !page.has_more {
+                return pages;
+            }
+            query = query.next_page();
+        }
+    }
+
+    #[test]
+    fn pages_split_the_matching_candidates() {
+        let provider = StaticCandidates::new(vec!["maya", "houdini", "Mari", "max", "nuke"]);
+        assert_eq!(
+            pages(&provider, "MA", 2),
+            vec![vec!["maya", "Mari"], vec!["max"]]
+        );
+        assert_eq!(pages(&provider, "", 5).len(), 1);
+        assert_eq!(pages(&provider, "katana", 5), vec![Vec::<String>::new()]);
+        assert!(provider.contains("max").unwrap());
+        assert!(!provider.contains("ma").unwrap());
+        assert_eq!(
+            collect_candidates(&provider, "m", 2).unwrap(),
+            vec!["maya", "Mari"]
+        );
+    }
+
+    #[test]
+    fn directories_provide_their_visible_subdirectories() {
+        let dir = candidates_dir("directories");
+        for name in &["nuke", "maya", "mari", "houdini", "max", ".git"] {
+            fs::create_dir(dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("manifest"), "not a package").unwrap();
+        let provider = DirectoryCandidates::new(&dir);
+        assert_eq!(
+            pages(&provider, "", 2),
+            vec![vec!["houdini", "mari"], vec!["max", "maya"], vec!["nuke"]]
+        );
+        // the pages are stable from one scan to the next
+        assert_eq!(pages(&provider, "", 2), pages(&provider.clone(), "", 2));
+        assert_eq!(
+            pages(&provider, "Ma", 10),
+            vec![vec!["mari", "max", "maya"]]
+        );
+        assert!(!provider.contains(".git").unwrap());
+        assert!(!provider.contains("manifest").unwrap());
+        assert!(DirectoryCandidates::new(dir.join("missing"))
+            .query(&CandidateQuery::new("", 10))
+            .is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn files_provide_their_lines_until_modified() {
+        let dir = candidates_dir("files");
+        let path = dir.join("packages.txt");
+        fs::write(&path, "# packages\nnuke\n\n  maya  \nmari\n").unwrap();
+        let provider = FileCandidates::new(&path);
+        assert_eq!(
+            pages(&provider, "", 2),
+            vec![vec!["nuke", "maya"], vec!["mari"]]
+        );
+        assert_eq!(pages(&provider, "MA", 10), vec![vec!["maya", "mari"]]);
+
+        // the listing is reread once the file's modification time changes
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, "houdini\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(pages(&provider, "", 10), vec![vec!["houdini"]]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,454 @@
+//! Notification of changes made to the contents of a WithsList.
+//!
+//! Clients register callbacks with the WithsList (eg `on_item_added`)
+//! which are invoked after the list has been edited, whether by the user
+//! or programmatically.
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Describes a single change to the contents of the list.
+///
+/// Each item held by a change, like the items passed to the callbacks, is
+/// in the form returned by `all_items`: its text, with disabled items
+/// carrying the DISABLED_PREFIX (eg `#~houdini-18.0.348`). Rows index all
+/// of the items, enabled or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WithsChange {
+    /// An item was added at the supplied row
+    Added { item: String, row: usize },
+    /// An item was removed from the supplied row
+    Removed { item: String, row: usize },
+    /// The items were reordered. Holds the items in their new order
+    Reordered { items: Vec<String> },
+    /// The item at the supplied row was enabled or disabled
+    Toggled {
+        item: String,
+        row: usize,
+        enabled: bool,
+    },
+    /// The contents were replaced wholesale, as by `set_items` or `clear`
+    Reset,
+}
+
+/// Callback invoked with the items of the list after any change
+pub type ItemsChangedCallback = Box<dyn Fn(&[String])>;
+/// Callback invoked with the name and row of an added or removed item
+pub type ItemCallback = Box<dyn Fn(&str, usize)>;
+/// Callback invoked with the items of the list after a reorder
+pub type ReorderedCallback = Box<dyn Fn(&[String])>;
+/// Callback invoked with the name and row of an item, and whether it is now
+/// enabled, after it has been enabled or disabled
+pub type ItemToggledCallback = Box<dyn Fn(&str, usize, bool)>;
+
+/// Callback invoked with the items when the user saves the list. Returns
+/// whether the items were successfully saved
+pub type SaveCallback = Box<dyn Fn(Vec<String>) -> bool>;
+// the save callback as held by the list, which is cloned out of its cell
+// so that it may be invoked without holding a borrow
+pub(crate) type SharedSaveCallback = Rc<dyn Fn(Vec<String>) -> bool>;
+
+/// Callback invoked with the selected items when the user triggers an
+/// action added to the context menu
+pub type ContextActionCallback = Box<dyn Fn(&[String])>;
+
+// the change callbacks as held by ChangeCallbacks, which shares them with
+// its clones
+type SharedItemsCallback = Rc<dyn Fn(&[String])>;
+type SharedItemCallback = Rc<dyn Fn(&str, usize)>;
+type SharedItemToggledCallback = Rc<dyn Fn(&str, usize, bool)>;
+
+/// Stores the registered callbacks and dispatches changes to them. The
+/// callbacks are shared, so that the list may clone them out of its cell
+/// and dispatch without holding a borrow, as a callback may itself edit
+/// the list or register further callbacks.
+#[derive(Default, Clone)]
+pub(crate) struct ChangeCallbacks {
+    items_changed: Vec<SharedItemsCallback>,
+    item_added: Vec<SharedItemCallback>,
+    item_removed: Vec<SharedItemCallback>,
+    reordered: Vec<SharedItemsCallback>,
+    item_toggled: Vec<SharedItemToggledCallback>,
+}
+
+impl ChangeCallbacks {
+    pub(crate) fn on_items_changed(&mut self, callback: ItemsChangedCallback) {
+        self.items_changed.push(Rc::from(callback));
+    }
+
+    pub(crate) fn on_item_added(&mut self, callback: ItemCallback) {
+        self.item_added.push(Rc::from(callback));
+    }
+
+    pub(crate) fn on_item_removed(&mut self, callback: ItemCallback) {
+        self.item_removed.push(Rc::from(callback));
+    }
+
+    pub(crate) fn on_reordered(&mut self, callback: ReorderedCallback) {
+        self.reordered.push(Rc::from(callback));
+    }
+
+    pub(crate) fn on_item_toggled(&mut self, callback: ItemToggledCallback) {
+        self.item_toggled.push(Rc::from(callback));
+    }
+
+    /// Invoke the callbacks interested in each of the supplied changes,
+    /// followed by the items changed callbacks, once.
+    ///
+    /// # Arguments
+    /// * `changes` - The changes that were made, in the order they were made
+    /// * `items` - The items of the list after the changes
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn dispatch(&self, changes: &[WithsChange], items: &[String]) {
+        for change in changes {
+            match change {
+                WithsChange::Added { item, row } => {
+                    self.item_added.iter().for_each(|cb| cb(item, *row));
+                }
+                WithsChange::Removed { item, row } => {
+                    self.item_removed.iter().for_each(|cb| cb(item, *row));
+                }
+                WithsChange::Reordered { items } => {
+                    self.reordered.iter().for_each(|cb| cb(items));
+                }
+                WithsChange::Toggled { item, row, enabled } => {
+                    self.item_toggled
+                        .iter()
+                        .for_each(|cb| cb(item, *row, *enabled));
+                }
+                WithsChange::Reset => (),
+            }
+        }
+        self.items_changed.iter().for_each(|cb| cb(items));
+    }
+}
+
+/// Describe the changes between two versions of the items. Removals are
+/// listed from the bottom up, followed by additions, followed by a reorder
+/// if the items common to both have moved.
+///
+/// # Arguments
+/// * `before` - The items prior to the change
+/// * `after` - The items following the change
+///
+/// # Returns
+/// * Vector of WithsChanges
+pub(crate) fn changes(before: &[String], after: &[String]) -> Vec<WithsChange> {
+    let before_set = before.iter().collect::<HashSet<_>>();
+    let after_set = after.iter().collect::<HashSet<_>>();
+    let mut changes = before
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, item)| !after_set.contains(item))
+        .map(|(row, item)| WithsChange::Removed {
+            item: item.clone(),
+            row,
+        })
+        .collect::<Vec<_>>();
+    changes.extend(
+        after
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !before_set.contains(item))
+            .map(|(row, item)| WithsChange::Added {
+                item: item.clone(),
+                row,
+            }),
+    );
+    let kept_before = before.iter().filter(|item| after_set.contains(item));
+    let kept_after = after.iter().filter(|item| before_set.contains(item));
+    if !kept_before.eq(kept_after) {
+        changes.push(WithsChange::Reordered {
+            items: after.to_vec(),
+        });
+    }
+    changes
+}
+
+/// An edit to the items, as recorded in the undo history. Rather than a
+/// snapshot of the items, it holds the changes made by the edit, along
+/// with the changes which revert it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Edit {
+    changes: Vec<WithsChange>,
+    reverts: Vec<WithsChange>,
+}
+
+impl Edit {
+    /// Describe the edit between two versions of the items
+    ///
+    /// # Arguments
+    /// * `before` - The items prior to the edit
+    /// * `after` - The items following the edit
+    ///
+    /// # Returns
+    /// * Edit
+    pub(crate) fn between(before: &[String], after: &[String]) -> Self {
+        Self {
+            changes: changes(before, after),
+            reverts: changes(after, before),
+        }
+    }
+
+    /// Determine whether the edit changed nothing
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Revert the edit
+    ///
+    /// # Arguments
+    /// * `items` - The items following the edit
+    ///
+    /// # Returns
+    /// * Vector of the items prior to the edit
+    pub(crate) fn undo(&self, items: &[String]) -> Vec<String> {
+        apply(items, &self.reverts)
+    }
+
+    /// Reapply the edit
+    ///
+    /// # Arguments
+    /// * `items` - The items prior to the edit
+    ///
+    /// # Returns
+    /// * Vector of the items following the edit
+    pub(crate) fn redo(&self, items: &[String]) -> Vec<String> {
+        apply(items, &self.changes)
+    }
+}
+
+// apply changes, as described by `changes`, to the items. The removals
+// are listed from the bottom up and the additions from the top down, so
+// that each row holds as the changes are applied in turn.
+fn apply(items: &[String], changes: &[WithsChange]) -> Vec<String> {
+    let mut items = items.to_vec();
+    for change in changes {
+        match change {
+            WithsChange::Removed { row, .. } if *row < items.len() => {
+                items.remove(*row);
+            }
+            WithsChange::Added { item, row } => {
+                items.insert((*row).min(items.len()), item.clone());
+            }
+            WithsChange::Reordered { items: reordered } => items = reordered.clone(),
+            _ => (),
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::withs_model::WithsModel;
+    use std::cell::RefCell;
+
+    // callbacks which log each invocation
+    fn logged() -> (ChangeCallbacks, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut callbacks = ChangeCallbacks::default();
+        let added = log.clone();
+        callbacks.on_item_added(Box::new(move |item, row| {
+            added.borrow_mut().push(format!("added {} {}", item, row))
+        }));
+        let removed = log.clone();
+        callbacks.on_item_removed(Box::new(move |item, row| {
+            removed
+                .borrow_mut()
+                .push(format!("removed {} {}", item, row))
+        }));
+        let reordered = log.clone();
+        callbacks.on_reordered(Box::new(move |items| {
+            reordered
+                .borrow_mut()
+                .push(format!("reordered {}", items.join(",")))
+        }));
+        let toggled = log.clone();
+        callbacks.on_item_toggled(Box::new(move |item, row, enabled| {
+            toggled
+                .borrow_mut()
+                .push(format!("toggled {} {} {}", item, row, enabled))
+        }));
+        let changed = log.clone();
+        callbacks.on_items_changed(Box::new(move |items| {
+            changed
+                .borrow_mut()
+                .push(format!("changed {}", items.join(",")))
+        }));
+        (callbacks, log)
+    }
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn changes_are_dispatched_in_order_followed_by_the_items() {
+        let (callbacks, log) = logged();
+        callbacks.dispatch(
+            &[
+                WithsChange::Removed {
+                    item: "maya".to_string(),
+                    row: 1,
+                },
+                WithsChange::Added {
+                    item: "nuke".to_string(),
+                    row: 0,
+                },
+                WithsChange::Toggled {
+                    item: "#~houdini".to_string(),
+                    row: 1,
+                    enabled: false,
+                },
+                WithsChange::Reordered {
+                    items: items(&["#~houdini", "nuke"]),
+                },
+            ],
+            &items(&["#~houdini", "nuke"]),
+        );
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "removed maya 1",
+                "added nuke 0",
+                "toggled #~houdini 1 false",
+                "reordered #~houdini,nuke",
+                "changed #~houdini,nuke",
+            ]
+        );
+    }
+
+    #[test]
+    fn resets_only_reach_the_items_changed_callbacks() {
+        let (callbacks, log) = logged();
+        callbacks.dispatch(&[WithsChange::Reset], &[]);
+        callbacks.dispatch(&[], &items(&["maya"]));
+        assert_eq!(*log.borrow(), vec!["changed ", "changed maya"]);
+    }
+
+    #[test]
+    fn clones_share_the_registered_callbacks() {
+        let (mut callbacks, log) = logged();
+        let cloned = callbacks.clone();
+        // callbacks registered after the clone are not shared
+        let late = log.clone();
+        callbacks.on_items_changed(Box::new(move |_| {
+            late.borrow_mut().push("late".to_string())
+        }));
+        cloned.dispatch(&[], &items(&["maya"]));
+        assert_eq!(*log.borrow(), vec!["changed maya"]);
+        callbacks.dispatch(&[], &items(&["nuke"]));
+        assert_eq!(*log.borrow(), vec!["changed maya", "changed nuke", "late"]);
+    }
+
+    #[test]
+    fn changes_between_edits_reach_the_callbacks() {
+        let mut model = WithsModel::from_items(vec!["a", "b", "c"]).expect("valid withs");
+        let before = model.all_items();
+        model.remove("b").unwrap();
+        model.add("d").unwrap();
+        model.move_item(0, 2).unwrap();
+        let changes = changes(&before, &model.all_items());
+        assert_eq!(
+            changes,
+            vec![
+                WithsChange::Removed {
+                    item: "b".to_string(),
+                    row: 1
+                },
+                WithsChange::Added {
+                    item: "d".to_string(),
+                    row: 1
+                },
+                WithsChange::Reordered {
+                    items: items(&["c", "d", "a"])
+                },
+            ]
+        );
+
+        let (callbacks, log) = logged();
+        callbacks.dispatch(&changes, &model.all_items());
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "removed b 1",
+                "added d 1",
+                "reordered c,d,a",
+                "changed c,d,a"
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_items_have_no_changes() {
+        let before = items(&["a", "b"]);
+        assert!(changes(&before, &before).is_empty());
+        assert_eq!(
+            changes(&before, &items(&["b", "a"])),
+            vec![WithsChange::Reordered {
+                items: items(&["b", "a"])
+            }]
+        );
+        // removals are listed from the bottom up
+        assert_eq!(
+            changes(&items(&["a", "b", "c"]), &items(&["b"])),
+            vec![
+                WithsChange::Removed {
+                    item: "c".to_string(),
+                    row: 2
+                },
+                WithsChange::Removed {
+                    item: "a".to_string(),
+                    row: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn edits_may_be_undone_and_redone() {
+        let cases = [
+            (vec!["a", "b", "c"], vec!["a", "c"]),
+            (vec!["a", "c"], vec!["a", "b", "c", "d"]),
+            (vec!["a", "b", "c"], vec!["c", "#~a", "d"]),
+            (vec!["a", "b", "c", "d"], vec!["d", "b", "e", "a"]),
+            (vec![], vec!["a"]),
+        ];
+        for (before, after) in cases.iter() {
+            let (before, after) = (items(before), items(after));
+            let edit = Edit::between(&before, &after);
+            assert!(!edit.is_empty());
+            assert_eq!(edit.redo(&before), after);
+            assert_eq!(edit.undo(&after), before);
+        }
+        assert!(Edit::between(&items(&["a"]), &items(&["a"])).is_empty());
+    }
+
+    #[test]
+    fn edits_hold_only_what_changed() {
+        let before = items(&["a", "b", "c", "d"]);
+        let edit = Edit::between(&before, &items(&["a", "b", "d"]));
+        assert_eq!(
+            edit,
+            Edit {
+                changes: vec![WithsChange::Removed {
+                    item: "c".to_string(),
+                    row: 2
+                }],
+                reverts: vec![WithsChange::Added {
+                    item: "c".to_string(),
+                    row: 2
+                }],
+            }
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Exchange of withs with the clipboard.
+//!
+//! Text pasted into a WithsList may hold any number of withs, separated by
+//! newlines, commas or whitespace, each with an optional version (eg
+//! `houdini-18.0.348, maya >= 2020`). Whitespace adjoining a comparison
+//! operator belongs to the version constraint, rather than separating
+//! withs. Each entry is validated as it would be were it entered in the
+//! combobox, and those which fail are reported rather than silently
+//! dropped.
+//!
+//! Withs copied from a WithsList are placed on the clipboard in three
+//! formats: plain text, with a with per line, a json array, and a json
+//! document of the WITHS_MIME_TYPE, which records the order and details of
+//! each with, and is preferred when pasting into another WithsList.
+use crate::list_item::ListItem;
+use crate::withs_model::ItemModel;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// The mime type of withs copied from a WithsList
+pub const WITHS_MIME_TYPE: &str = "application/x-pbgui-withs";
+/// The mime type of withs copied as a json array
+pub const JSON_MIME_TYPE: &str = "application/json";
+// the version of the WITHS_MIME_TYPE document
+const WITHS_MIME_VERSION: u64 = 1;
+
+/// The reason a pasted entry was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteRejection {
+    /// The entry could not be parsed, along with the reason
+    Invalid(String),
+    /// The package is not one of the candidates
+    Unknown,
+    /// The package is already in the list, or appears earlier in the paste,
+    /// and may not appear more than once
+    Duplicate,
+}
+
+impl fmt::Display for PasteRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "{}", e),
+            Self::Unknown => write!(f, "not a known package"),
+            Self::Duplicate => write!(f, "already in the list"),
+        }
+    }
+}
+
+/// The outcome of a paste
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PasteReport {
+    /// The entries which were added, in order
+    pub added: Vec<String>,
+    /// The entries which were rejected, along with the reason
+    pub rejected: Vec<(String, PasteRejection)>,
+}
+
+impl PasteReport {
+    /// Determine whether all of the entries were added
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_complete(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Split pasted text into its entries. Entries are separated by newlines
+/// or commas, and within a line, by whitespace, other than whitespace
+/// adjoining a comparison operator (eg `maya >= 2020` is a single entry).
+///
+/// # Arguments
+/// * `text` - The pasted text
+///
+/// # Returns
+/// * Vector of entries, which may or may not be valid
+pub fn split_entries(text: &str) -> Vec<String> {
+    let is_op = |c: char| "<>=".contains(c);
+    let mut entries = Vec::<String>::new();
+    for line in text.split([',', '\n', '\r']) {
+        let start = entries.len();
+        for token in line.split_whitespace() {
+            // a token starting with an operator continues the previous
+            // entry, as does any token following one ending with an operator
+            match entries[start..].last_mut() {
+                Some(entry) if entry.ends_with(is_op) || token.starts_with(is_op) => {
+                    entry.push_str(token)
+                }
+                _ => entries.push(token.to_string()),
+            }
+        }
+    }
+    entries
+}
+
+/// Validate pasted entries, sorting them into those which may be added and
+/// those which are rejected
+///
+/// # Arguments
+/// * `entries` - The entries, as returned by `split_entries`
+/// * `existing` - The ItemModel of the list, whose rules determine which
+///   entries are duplicates
+/// * `is_candidate` - Determines whether a package name is known
+///
+/// # Returns
+/// * PasteReport whose `added` holds the entries which may be added
+pub fn validate_entries<T, F>(
+    entries: Vec<String>,
+    existing: &ItemModel<T>,
+    is_candidate: F,
+) -> PasteReport
+where
+    T: ListItem,
+    F: Fn(&str) -> bool,
+{
+    let mut model = existing.clone();
+    let mut report = PasteReport::default();
+    for text in entries {
+        let entry = match T::parse_item(&text) {
+            Ok(entry) => entry,
+            Err(e) => {
+                report
+                    .rejected
+                    .push((text, PasteRejection::Invalid(e.to_string())));
+                continue;
+            }
+        };
+        if !is_candidate(entry.key()) {
+            report.rejected.push((text, PasteRejection::Unknown));
+        } else if model.insert_entry(model.len(), entry.clone()).is_err() {
+            report.rejected.push((text, PasteRejection::Duplicate));
+        } else {
+            report.added.push(entry.to_string());
+        }
+    }
+    report
+}
+
+/// Format the entries as plain text, with an entry per line
+///
+/// # Arguments
+/// * `entries` - The entries, in order
+///
+/// # Returns
+/// * String
+pub fn to_plain_text<T: fmt::Display>(entries: &[T]) -> String {
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
+}
+
+/// Format the entries as a json array of strings
+///
+/// # Arguments
+/// * `entries` - The entries, in order
+///
+/// # Returns
+/// * String
+pub fn to_json<T: fmt::Display>(entries: &[T]) -> String {
+    Value::from(entries.iter().map(ToString::to_string).collect::<Vec<_>>()).to_string()
+}
+
+/// Encode the entries, along with the rows they were copied from, as a
+/// WITHS_MIME_TYPE document. Each entry records its text, its key as the
+/// `name`, and any details of the item (eg the `op` and `version` of a with).
+///
+/// # Arguments
+/// * `entries` - The rows and entries, in order
+///
+/// # Returns
+/// * String holding the json document
+pub fn encode_withs<T: ListItem>(entries: &[(usize, T)]) -> String {
+    let items = entries
+        .iter()
+        .map(|(row, entry)| {
+            let mut item = Map::new();
+            item.insert("item".to_string(), json!(entry.to_string()));
+            item.insert("name".to_string(), json!(entry.key()));
+            for (field, value) in entry.details() {
+                item.insert(field.to_string(), json!(value));
+            }
+            item.insert("enabled".to_string(), json!(entry.is_enabled()));
+            item.insert("row".to_string(), json!(row));
+            Value::Object(item)
+        })
+        .collect::<Vec<_>>();
+    json!({ "version": WITHS_MIME_VERSION, "items": items }).to_string()
+}
+
+/// Decode a WITHS_MIME_TYPE document
+///
+/// # Arguments
+/// * `data` - The json document
+///
+/// # Returns
+/// * Some(Vector of items, in order), or None if the document is malformed
+pub fn decode_withs(data: &str) -> Option<Vec<String>> {
+    let document = serde_json::from_str::<Value>(data).ok()?;
+    if document.get("version")?.as_u64()? > WITHS_MIME_VERSION {
+        return None;
+    }
+    document
+        .get("items")?
+        .as_array()?
+        .iter()
+        .map(|item| item.get("item")?.as_str().map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::with_entry::WithEntry;
+    use crate::withs_model::WithsModel;
+
+    #[test]
+    fn split_entries_separates_lines_commas_and_whitespace() {
+        assert_eq!(
+            split_entries("houdini-18.0.348, maya\r\nnuke  katana\n\n,mari"),
+            vec!["houdini-18.0.348", "maya", "nuke", "katana", "mari"]
+        );
+        assert!(split_entries(" \n , ").is_empty());
+    }
+
+    #[test]
+    fn split_entries_keeps_version_constraints_together() {
+        assert_eq!(
+            split_entries("maya >= 2020\nhoudini>= 18.0 nuke <12\nkatana == 3.5, mari"),
+            vec![
+                "maya>=2020",
+                "houdini>=18.0",
+                "nuke<12",
+                "katana==3.5",
+                "mari"
+            ]
+        );
+        let report = validate_entries(
+            split_entries("maya >= 2020, houdini"),
+            &WithsModel::new(),
+            |_| true,
+        );
+        assert!(report.is_complete());
+        assert_eq!(report.added, vec!["maya>=2020", "houdini"]);
+    }
+
+    #[test]
+    fn encode_withs_records_the_details_of_each_with() {
+        let entries = vec![
+            (2, WithEntry::parse("maya>=2020").expect("valid with")),
+            (0, WithEntry::parse("#~houdini").expect("valid with")),
+        ];
+        let document = serde_json::from_str::<Value>(&encode_withs(&entries)).expect("json");
+        let items = document["items"].as_array().expect("items");
+        assert_eq!(items[0]["name"], "maya");
+        assert_eq!(items[0]["op"], ">=");
+        assert_eq!(items[0]["version"], "2020");
+        assert_eq!(items[0]["row"], 2);
+        assert_eq!(items[1]["version"], Value::Null);
+        assert_eq!(items[1]["enabled"], false);
+        assert_eq!(
+            decode_withs(&encode_withs(&entries)),
+            Some(vec!["maya>=2020".to_string(), "#~houdini".to_string()])
+        );
+    }
+}
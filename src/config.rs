@@ -0,0 +1,875 @@
+//! Configuration of the ItemList, and so of the WithsList.
+//!
+//! An ItemListConfig may be assembled in code, via its builder methods, or
+//! loaded from settings files, in toml or json. Settings are typically
+//! layered, with a site wide file overridden by a user's file, in turn
+//! overridden by a file for the application, each of which need only hold
+//! the settings it changes:
+//!
+//! ```toml
+//! default_mode = "find"
+//! max_visible_items = 20
+//! stylesheet = "withs.qss"
+//! allowed_duplicates = ["houdini_plugin"]
+//!
+//! [shortcuts]
+//! move_up = ["Alt+Up", "Alt+K"]
+//! find_mode = "Ctrl+F"
+//!
+//! [labels]
+//! add = "Add With"
+//! ```
+//!
+//! Unknown keys are rejected, rather than ignored, so that typos are caught.
+use crate::bindings::{Action, BindingError, KeyBindings};
+use serde_json::{Map, Value};
+use std::env;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// the keys recognized at the top level of a settings file
+const KEYS: [&str; 6] = [
+    "shortcuts",
+    "labels",
+    "max_visible_items",
+    "stylesheet",
+    "default_mode",
+    "allowed_duplicates",
+];
+// the keys recognized within the labels table
+const LABEL_KEYS: [&str; 3] = ["add", "find", "filter"];
+
+/// Errors returned when loading settings
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Unable to read the file
+    Io { path: PathBuf, error: io::Error },
+    /// The format could not be determined from the file's extension
+    UnknownFormat(PathBuf),
+    /// A toml document is malformed
+    Toml {
+        origin: String,
+        error: toml::de::Error,
+    },
+    /// A json document is malformed
+    Json {
+        origin: String,
+        error: serde_json::Error,
+    },
+    /// A key is not recognized. Holds the dotted path of the key, and the
+    /// keys which are recognized in its place
+    UnknownKey {
+        origin: String,
+        key: String,
+        expected: Vec<&'static str>,
+    },
+    /// A value is of the wrong type, or out of range
+    InvalidValue {
+        origin: String,
+        key: String,
+        expected: &'static str,
+    },
+    /// A shortcut is invalid, or collides with another
+    Binding { origin: String, error: BindingError },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::UnknownFormat(path) => write!(
+                f,
+                "unable to determine the format of '{}'. Expected a .toml or .json file",
+                path.display()
+            ),
+            Self::Toml { origin, error } => write!(f, "{}: malformed toml: {}", origin, error),
+            Self::Json { origin, error } => write!(f, "{}: malformed json: {}", origin, error),
+            Self::UnknownKey {
+                origin,
+                key,
+                expected,
+            } => write!(
+                f,
+                "{}: unknown key '{}'. Expected one of: {}",
+                origin,
+                key,
+                expected.join(", ")
+            ),
+            Self::InvalidValue {
+                origin,
+                key,
+                expected,
+            } => write!(
+                f,
+                "{}: invalid value for '{}'. Expected {}",
+                origin, key, expected
+            ),
+            Self::Binding { origin, error } => write!(f, "{}: {}", origin, error),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Toml { error, .. } => Some(error),
+            Self::Json { error, .. } => Some(error),
+            Self::Binding { error, .. } => Some(error),
+            Self::UnknownFormat(_) | Self::UnknownKey { .. } | Self::InvalidValue { .. } => None,
+        }
+    }
+}
+
+/// The formats in which settings may be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsFormat {
+    Toml,
+    Json,
+}
+
+impl SettingsFormat {
+    /// Determine the format of a file from its extension
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Some(SettingsFormat), or None if the extension is not recognized
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("toml") => Some(Self::Toml),
+            Some("json") => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The mode the ItemList starts out in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListMode {
+    /// The combobox adds items to the list
+    #[default]
+    Add,
+    /// The combobox finds items in the list
+    Find,
+    /// The combobox filters the items of the list
+    Filter,
+}
+
+impl FromStr for ListMode {
+    type Err = UnknownListMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "add" => Ok(Self::Add),
+            "find" => Ok(Self::Find),
+            "filter" => Ok(Self::Filter),
+            _ => Err(UnknownListMode(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when parsing a ListMode from text which names none of
+/// the modes. Holds the text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownListMode(pub String);
+
+impl fmt::Display for UnknownListMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown mode '{}'. Expected one of: add, find, filter",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownListMode {}
+
+/// Struct which holds configuration for the ItemList
+#[derive(Debug, Clone)]
+pub struct ItemListConfig {
+    /// The key sequences bound to each of the actions
+    bindings: KeyBindings,
+    /// The label of the combobox in add mode
+    add_label: String,
+    /// The label of the combobox in find mode
+    find_label: String,
+    /// The label of the combobox in filter mode
+    filter_label: String,
+    /// The maximum number of items displayed by the combobox's dropdown
+    max_visible_items: Option<i32>,
+    /// The path of the stylesheet applied to the ItemList
+    stylesheet: Option<PathBuf>,
+    /// The mode the ItemList starts out in
+    default_mode: ListMode,
+    /// The keys which may appear more than once
+    allowed_duplicates: Vec<String>,
+}
+
+/// The configuration of the WithsList
+pub type WithsListConfig = ItemListConfig;
+
+impl Default for ItemListConfig {
+    fn default() -> Self {
+        Self {
+            bindings: KeyBindings::default(),
+            add_label: "Add Item".to_string(),
+            find_label: "Find Item".to_string(),
+            filter_label: "Filter Items".to_string(),
+            max_visible_items: None,
+            stylesheet: None,
+            default_mode: ListMode::default(),
+            allowed_duplicates: Vec::new(),
+        }
+    }
+}
+
+impl ItemListConfig {
+    /// Load the config from a settings file, in toml or json, as indicated
+    /// by its extension. Settings absent from the file take their defaults.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the settings file
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemListConfig, or a ConfigError
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        config.merge_file(path)?;
+        Ok(config)
+    }
+
+    /// Load the config from layers of settings files, each overriding the
+    /// settings of those preceding it. Files which do not exist are skipped.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths of the settings files, from least to most specific
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemListConfig, or the first ConfigError
+    pub fn from_layers<P: AsRef<Path>>(paths: &[P]) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for path in paths.iter().map(AsRef::as_ref) {
+            if path.exists() {
+                config.merge_file(path)?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Retrieve the conventional layers of settings files for an
+    /// application: the site file, named by the `PBGUI_WITHS_SITE_CONFIG`
+    /// environment variable, or `/etc/pbgui-withs/withs.toml`, followed by
+    /// the user's `~/.config/pbgui-withs/withs.toml`, followed by the
+    /// application's `~/.config/pbgui-withs/<app>.toml`
+    ///
+    /// # Arguments
+    /// * `app` - The name of the application
+    ///
+    /// # Returns
+    /// * Vector of paths, from least to most specific, for `from_layers`
+    pub fn layer_paths(app: &str) -> Vec<PathBuf> {
+        let mut paths = vec![env::var_os("PBGUI_WITHS_SITE_CONFIG").map_or_else(
+            || PathBuf::from("/etc/pbgui-withs/withs.toml"),
+            PathBuf::from,
+        )];
+        if let Some(home) = env::var_os("HOME") {
+            let dir = PathBuf::from(home).join(".config").join("pbgui-withs");
+            paths.push(dir.join("withs.toml"));
+            paths.push(dir.join(format!("{}.toml", app)));
+        }
+        paths
+    }
+
+    /// Override the config with the settings held by a file. Relative
+    /// stylesheet paths are resolved against the file's directory. The
+    /// config is left untouched if any of the settings is invalid.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the settings file
+    ///
+    /// # Returns
+    /// * Ok, or a ConfigError
+    pub fn merge_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let format = SettingsFormat::from_path(path)
+            .ok_or_else(|| ConfigError::UnknownFormat(path.into()))?;
+        let text = fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path.into(),
+            error,
+        })?;
+        self.merge(&text, format, &path.display().to_string(), path.parent())
+    }
+
+    /// Override the config with the settings held by a string. The config
+    /// is left untouched if any of the settings is invalid.
+    ///
+    /// # Arguments
+    /// * `text` - The settings
+    /// * `format` - The SettingsFormat of the text
+    ///
+    /// # Returns
+    /// * Ok, or a ConfigError
+    pub fn merge_str(&mut self, text: &str, format: SettingsFormat) -> Result<(), ConfigError> {
+        self.merge(text, format, "<settings>", None)
+    }
+
+    /// Builder style method replacing the key bindings
+    ///
+    /// # Arguments
+    /// * `bindings` - The KeyBindings
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_bindings(mut self, bindings: KeyBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
+    /// Builder style method binding sequences to an action, replacing its
+    /// existing sequences
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    /// * `sequences` - The key sequences, as &str or String (eg `Ctrl+F`)
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemListConfig, or a BindingError
+    pub fn with_binding<S>(mut self, action: Action, sequences: &[S]) -> Result<Self, BindingError>
+    where
+        S: AsRef<str>,
+    {
+        self.bindings.bind(action, sequences)?;
+        Ok(self)
+    }
+
+    /// Builder style method setting the label of the combobox in add mode
+    ///
+    /// # Arguments
+    /// * `label` - The label
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_add_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.add_label = label.into();
+        self
+    }
+
+    /// Builder style method setting the label of the combobox in find mode
+    ///
+    /// # Arguments
+    /// * `label` - The label
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_find_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.find_label = label.into();
+        self
+    }
+
+    /// Builder style method setting the label of the combobox in filter mode
+    ///
+    /// # Arguments
+    /// * `label` - The label
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_filter_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.filter_label = label.into();
+        self
+    }
+
+    /// Builder style method setting the maximum number of items displayed
+    /// by the combobox's dropdown
+    ///
+    /// # Arguments
+    /// * `max` - The maximum number of items
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_max_visible_items(mut self, max: i32) -> Self {
+        self.max_visible_items = Some(max);
+        self
+    }
+
+    /// Builder style method setting the path of the stylesheet
+    ///
+    /// # Arguments
+    /// * `path` - The path of the stylesheet
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_stylesheet<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.stylesheet = Some(path.into());
+        self
+    }
+
+    /// Builder style method setting the mode the ItemList starts out in
+    ///
+    /// # Arguments
+    /// * `mode` - The ListMode
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_default_mode(mut self, mode: ListMode) -> Self {
+        self.default_mode = mode;
+        self
+    }
+
+    /// Builder style method setting the keys which may appear more than
+    /// once, as long as each appearance has different text (eg a different
+    /// version)
+    ///
+    /// # Arguments
+    /// * `keys` - Vector of keys, as &str or String
+    ///
+    /// # Returns
+    /// * The ItemListConfig
+    pub fn with_allowed_duplicates<I: Into<String>>(mut self, keys: Vec<I>) -> Self {
+        self.allowed_duplicates = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Retrieve the key bindings
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &KeyBindings
+    pub fn bindings(&self) -> &KeyBindings {
+        &self.bindings
+    }
+
+    /// Retrieve the key bindings mutably, in order to rebind actions
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &mut KeyBindings
+    pub fn bindings_mut(&mut self) -> &mut KeyBindings {
+        &mut self.bindings
+    }
+
+    /// Retrieve the label of the combobox in add mode
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn add_label(&self) -> &str {
+        &self.add_label
+    }
+
+    /// Retrieve the label of the combobox in find mode
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn find_label(&self) -> &str {
+        &self.find_label
+    }
+
+    /// Retrieve the label of the combobox in filter mode
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn filter_label(&self) -> &str {
+        &self.filter_label
+    }
+
+    /// Retrieve the maximum number of items displayed by the combobox's
+    /// dropdown
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(max), or None to use qt's default
+    pub fn max_visible_items(&self) -> Option<i32> {
+        self.max_visible_items
+    }
+
+    /// Retrieve the path of the stylesheet
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(&Path), or None if there is no stylesheet
+    pub fn stylesheet(&self) -> Option<&Path> {
+        self.stylesheet.as_deref()
+    }
+
+    /// Retrieve the mode the ItemList starts out in
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * ListMode
+    pub fn default_mode(&self) -> ListMode {
+        self.default_mode
+    }
+
+    /// Retrieve the keys which may appear more than once
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Slice of keys
+    pub fn allowed_duplicates(&self) -> &[String] {
+        &self.allowed_duplicates
+    }
+
+    // parse the settings, applying them to a copy of the config, which
+    // replaces the config once all of the settings have been applied
+    fn merge(
+        &mut self,
+        text: &str,
+        format: SettingsFormat,
+        origin: &str,
+        dir: Option<&Path>,
+    ) -> Result<(), ConfigError> {
+        let settings = match format {
+            SettingsFormat::Toml => toml::from_str::<toml::Value>(text)
+                .map_err(|error| ConfigError::Toml {
+                    origin: origin.to_string(),
+                    error,
+                })
+                .and_then(|value| {
+                    serde_json::to_value(value).map_err(|error| ConfigError::Json {
+                        origin: origin.to_string(),
+                        error,
+                    })
+                })?,
+            SettingsFormat::Json => {
+                serde_json::from_str::<Value>(text).map_err(|error| ConfigError::Json {
+                    origin: origin.to_string(),
+                    error,
+                })?
+            }
+        };
+        let mut config = self.clone();
+        Layer { origin, dir }.apply(&settings, &mut config)?;
+        *self = config;
+        Ok(())
+    }
+}
+
+// a settings file being applied to a config
+struct Layer<'a> {
+    origin: &'a str,
+    dir: Option<&'a Path>,
+}
+
+impl<'a> Layer<'a> {
+    fn apply(&self, settings: &Value, config: &mut ItemListConfig) -> Result<(), ConfigError> {
+        for (key, value) in self.table("", settings)? {
+            match key.as_str() {
+                "shortcuts" => self.apply_shortcuts(value, &mut config.bindings)?,
+                "labels" => {
+                    for (label, text) in self.table(key, value)? {
+                        let key = format!("labels.{}", label);
+                        let text = self.string(&key, text)?;
+                        match label.as_str() {
+                            "add" => config.add_label = text,
+                            "find" => config.find_label = text,
+                            "filter" => config.filter_label = text,
+                            _ => return Err(self.unknown_key(key, LABEL_KEYS.to_vec())),
+                        }
+                    }
+                }
+                "max_visible_items" => {
+                    let max = value
+                        .as_u64()
+                        .filter(|max| *max > 0 && *max <= i32::MAX as u64)
+                        .ok_or_else(|| self.invalid_value(key, "a positive integer"))?;
+                    config.max_visible_items = Some(max as i32);
+                }
+                "stylesheet" => {
+                    let path = PathBuf::from(self.string(key, value)?);
+                    config.stylesheet = Some(match self.dir {
+                        Some(dir) if path.is_relative() => dir.join(path),
+                        _ => path,
+                    });
+                }
+                "default_mode" => {
+                    config.default_mode = value
+                        .as_str()
+                        .and_then(|mode| mode.parse().ok())
+                        .ok_or_else(|| self.invalid_value(key, "one of: add, find, filter"))?;
+                }
+                "allowed_duplicates" => config.allowed_duplicates = self.strings(key, value)?,
+                _ => return Err(self.unknown_key(key.clone(), KEYS.to_vec())),
+            }
+        }
+        Ok(())
+    }
+
+    // the actions of the layer are unbound before any is rebound, so that
+    // the layer may swap sequences between actions
+    fn apply_shortcuts(
+        &self,
+        value: &Value,
+        bindings: &mut KeyBindings,
+    ) -> Result<(), ConfigError> {
+        let mut shortcuts = Vec::new();
+        for (name, sequences) in self.table("shortcuts", value)? {
+            let key = format!("shortcuts.{}", name);
+            let action = name.parse::<Action>().map_err(|_| {
+                self.unknown_key(
+                    key.clone(),
+                    Action::ALL.iter().map(Action::as_str).collect(),
+                )
+            })?;
+            let sequences = match sequences {
+                Value::String(sequence) => vec![sequence.clone()],
+                _ => self.strings(&key, sequences)?,
+            };
+            shortcuts.push((action, sequences));
+        }
+        for (action, _) in &shortcuts {
+            bindings.unbind(*action);
+        }
+        for (action, sequences) in &shortcuts {
+            bindings
+                .bind(*action, sequences)
+                .map_err(|error| ConfigError::Binding {
+                    origin: self.origin.to_string(),
+                    error,
+                })?;
+        }
+        Ok(())
+    }
+
+    fn table<'v>(
+        &self,
+        key: &str,
+        value: &'v Value,
+    ) -> Result<&'v Map<String, Value>, ConfigError> {
+        value.as_object().ok_or_else(|| {
+            self.invalid_value(if key.is_empty() { "<root>" } else { key }, "a table")
+        })
+    }
+
+    fn string(&self, key: &str, value: &Value) -> Result<String, ConfigError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| self.invalid_value(key, "a string"))
+    }
+
+    fn strings(&self, key: &str, value: &Value) -> Result<Vec<String>, ConfigError> {
+        value
+            .as_array()
+            .and_then(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| self.invalid_value(key, "an array of strings"))
+    }
+
+    fn unknown_key(&self, key: String, expected: Vec<&'static str>) -> ConfigError {
+        ConfigError::UnknownKey {
+            origin: self.origin.to_string(),
+            key,
+            expected,
+        }
+    }
+
+    fn invalid_value(&self, key: &str, expected: &'static str) -> ConfigError {
+        ConfigError::InvalidValue {
+            origin: self.origin.to_string(),
+            key: key.to_string(),
+            expected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::KeySequence;
+
+    // a fresh directory for the settings files of a test
+    fn settings_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "pbgui-withs-config-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, text: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn sequence(text: &str) -> KeySequence {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let dir = settings_dir("layers");
+        let site = write(
+            &dir,
+            "site.toml",
+            "default_mode = \"find\"\nmax_visible_items = 10\n\n[labels]\nadd = \"Site\"\nfind = \"Site Find\"\n",
+        );
+        let user = write(
+            &dir,
+            "user.json",
+            r#"{"max_visible_items": 20, "shortcuts": {"move_up": ["Alt+K"]}}"#,
+        );
+        let app = write(&dir, "app.toml", "[labels]\nadd = \"App\"\n");
+        let missing = dir.join("missing.toml");
+        let config = ItemListConfig::from_layers(&[site, user, missing, app]).unwrap();
+        // each setting takes the value of the last layer holding it
+        assert_eq!(config.add_label(), "App");
+        assert_eq!(config.find_label(), "Site Find");
+        assert_eq!(config.max_visible_items(), Some(20));
+        assert_eq!(config.default_mode(), ListMode::Find);
+        // settings held by no layer keep their defaults
+        assert_eq!(config.filter_label(), "Filter Items");
+        assert_eq!(config.stylesheet(), None);
+        assert_eq!(
+            config.bindings().sequences(Action::MoveUp),
+            &[sequence("Alt+K")]
+        );
+        assert_eq!(
+            config.bindings().sequences(Action::MoveDown),
+            &[sequence("Alt+Down")]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn layers_may_swap_shortcuts_between_actions() {
+        let mut config = ItemListConfig::default();
+        config
+            .merge_str(
+                "[shortcuts]\nfind_mode = \"Ctrl+Shift+F\"\nfilter_mode = \"Ctrl+F\"\n",
+                SettingsFormat::Toml,
+            )
+            .unwrap();
+        assert_eq!(
+            config.bindings().action(&sequence("Ctrl+F")),
+            Some(Action::FilterMode)
+        );
+        assert_eq!(
+            config.bindings().action(&sequence("Ctrl+Shift+F")),
+            Some(Action::FindMode)
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected_leaving_the_config_untouched() {
+        let mut config = ItemListConfig::default().with_add_label("Add With");
+        // each of the settings holds a valid label alongside the unknown key
+        for (text, unknown) in &[
+            ("colour = \"red\"\n[labels]\nadd = \"Changed\"\n", "colour"),
+            ("[labels]\nadd = \"Changed\"\nad = \"Add\"\n", "labels.ad"),
+            (
+                "[labels]\nadd = \"Changed\"\n[shortcuts]\njump = \"Ctrl+J\"\n",
+                "shortcuts.jump",
+            ),
+        ] {
+            match config.merge_str(text, SettingsFormat::Toml) {
+                Err(ConfigError::UnknownKey { key, .. }) => assert_eq!(key, *unknown),
+                other => panic!("expected an unknown key, got {:?}", other),
+            }
+            assert_eq!(config.add_label(), "Add With");
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let mut config = ItemListConfig::default();
+        for text in &[
+            "max_visible_items = 0",
+            "default_mode = \"replace\"",
+            "allowed_duplicates = \"maya\"",
+            "labels = \"Add\"",
+        ] {
+            assert!(
+                matches!(
+                    config.merge_str(text, SettingsFormat::Toml),
+                    Err(ConfigError::InvalidValue { .. })
+                ),
+                "{}",
+                text
+            );
+        }
+        assert!(matches!(
+            config.merge_str("[shortcuts]\nundo = \"Ctrl+F\"\n", SettingsFormat::Toml),
+            Err(ConfigError::Binding { .. })
+        ));
+        assert!(matches!(
+            ItemListConfig::from_file("withs.yaml"),
+            Err(ConfigError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn relative_stylesheets_resolve_against_their_file() {
+        let dir = settings_dir("stylesheet");
+        let relative = write(&dir, "relative.toml", "stylesheet = \"qss/withs.qss\"\n");
+        let config = ItemListConfig::from_file(&relative).unwrap();
+        assert_eq!(
+            config.stylesheet(),
+            Some(dir.join("qss/withs.qss").as_path())
+        );
+
+        let absolute = write(
+            &dir,
+            "absolute.json",
+            r#"{"stylesheet": "/site/withs.qss"}"#,
+        );
+        let config = ItemListConfig::from_file(&absolute).unwrap();
+        assert_eq!(config.stylesheet(), Some(Path::new("/site/withs.qss")));
+
+        // strings have no directory to resolve against
+        let mut config = ItemListConfig::default();
+        config
+            .merge_str("stylesheet = \"withs.qss\"", SettingsFormat::Toml)
+            .unwrap();
+        assert_eq!(config.stylesheet(), Some(Path::new("withs.qss")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn list_modes_parse_from_their_names() {
+        assert_eq!("filter".parse::<ListMode>(), Ok(ListMode::Filter));
+        assert_eq!(
+            "Find".parse::<ListMode>(),
+            Err(UnknownListMode("Find".to_string()))
+        );
+    }
+}
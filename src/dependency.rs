@@ -0,0 +1,273 @@
+//! Relationships between packages.
+//!
+//! A DependencyResolver reports the packages which a package depends upon.
+//! The WithsList consults it when an item is added, offering to add any
+//! dependencies which are missing, and marks the items which are implied
+//! by others. Dependencies are expressed as package names; the versions of
+//! the items are ignored when comparing them.
+use crate::with_entry::WithEntry;
+use std::collections::{HashMap, HashSet};
+
+/// Reports the direct dependencies of packages
+pub trait DependencyResolver {
+    /// Retrieve the packages which the supplied package depends upon directly
+    ///
+    /// # Arguments
+    /// * `package` - The package name
+    ///
+    /// # Returns
+    /// * Vector of package names
+    fn dependencies(&self, package: &str) -> Vec<String>;
+}
+
+/// A DependencyResolver backed by an in memory map of package name to its
+/// direct dependencies
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MapResolver {
+    dependencies: HashMap<String, Vec<String>>,
+}
+
+impl MapResolver {
+    /// New up an empty MapResolver
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MapResolver instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the direct dependencies of a package, replacing any existing ones
+    ///
+    /// # Arguments
+    /// * `package` - The package name
+    /// * `dependencies` - Vector of package names
+    ///
+    /// # Returns
+    /// * None
+    pub fn insert<I>(&mut self, package: &str, dependencies: Vec<I>)
+    where
+        I: Into<String>,
+    {
+        self.dependencies.insert(
+            package.to_string(),
+            dependencies.into_iter().map(Into::into).collect(),
+        );
+    }
+
+    /// Builder style variant of `insert`
+    ///
+    /// # Arguments
+    /// * `package` - The package name
+    /// * `dependencies` - Vector of package names
+    ///
+    /// # Returns
+    /// * The MapResolver
+    pub fn with_dependencies<I>(mut self, package: &str, dependencies: Vec<I>) -> Self
+    where
+        I: Into<String>,
+    {
+        self.insert(package, dependencies);
+        self
+    }
+}
+
+impl DependencyResolver for MapResolver {
+    fn dependencies(&self, package: &str) -> Vec<String> {
+        self.dependencies.get(package).cloned().unwrap_or_default()
+    }
+}
+
+/// Retrieve all of the packages which the supplied package depends upon,
+/// directly or indirectly, ordered so that each package follows its own
+/// dependencies. Cycles are tolerated.
+///
+/// # Arguments
+/// * `resolver` - The DependencyResolver
+/// * `package` - The package name
+///
+/// # Returns
+/// * Vector of package names, excluding the package itself
+pub fn transitive_dependencies(resolver: &dyn DependencyResolver, package: &str) -> Vec<String> {
+    let mut visited = HashSet::new();
+    visited.insert(package.to_string());
+    let mut ordered = Vec::new();
+    for dependency in resolver.dependencies(package) {
+        visit(resolver, dependency, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
+/// Retrieve the dependencies of the item which are absent from the items
+///
+/// # Arguments
+/// * `resolver` - The DependencyResolver
+/// * `item` - The item, which may carry a version
+/// * `items` - The items of the list
+///
+/// # Returns
+/// * Vector of package names, ordered so that each follows its own dependencies
+pub fn missing_dependencies(
+    resolver: &dyn DependencyResolver,
+    item: &str,
+    items: &[String],
+) -> Vec<String> {
+    let present = package_names(items);
+    transitive_dependencies(resolver, &package_name(item))
+        .into_iter()
+        .filter(|dependency| !present.contains(dependency))
+        .collect()
+}
+
+/// Expand the items to include all of their dependencies. Each missing
+/// dependency is inserted ahead of the first item which requires it.
+///
+/// # Arguments
+/// * `resolver` - The DependencyResolver
+/// * `items` - The items of the list
+///
+/// # Returns
+/// * Vector of items and package names
+pub fn resolve(resolver: &dyn DependencyResolver, items: &[String]) -> Vec<String> {
+    let mut present = package_names(items);
+    let mut resolved = Vec::with_capacity(items.len());
+    for item in items {
+        for dependency in transitive_dependencies(resolver, &package_name(item)) {
+            if present.insert(dependency.clone()) {
+                resolved.push(dependency);
+            }
+        }
+        resolved.push(item.clone());
+    }
+    resolved
+}
+
+/// Determine which of the items are implied by others, as they are
+/// dependencies of other items in the list
+///
+/// # Arguments
+/// * `resolver` - The DependencyResolver
+/// * `items` - The items of the list
+///
+/// # Returns
+/// * Map of each implied item to the items which imply it, in list order
+pub fn implied_items(
+    resolver: &dyn DependencyResolver,
+    items: &[String],
+) -> HashMap<String, Vec<String>> {
+    let names = items
+        .iter()
+        .map(|item| (package_name(item), item))
+        .collect::<HashMap<_, _>>();
+    let mut implied = HashMap::<String, Vec<String>>::new();
+    for item in items {
+        for dependency in transitive_dependencies(resolver, &package_name(item)) {
+            if let Some(implied_item) = names.get(&dependency) {
+                implied
+                    .entry((*implied_item).clone())
+                    .or_default()
+                    .push(item.clone());
+            }
+        }
+    }
+    implied
+}
+
+// depth first traversal, recording each package after its dependencies
+fn visit(
+    resolver: &dyn DependencyResolver,
+    package: String,
+    visited: &mut HashSet<String>,
+    ordered: &mut Vec<String>,
+) {
+    if !visited.insert(package.clone()) {
+        return;
+    }
+    for dependency in resolver.dependencies(&package) {
+        visit(resolver, dependency, visited, ordered);
+    }
+    ordered.push(package);
+}
+
+// the package name of an item, ignoring any version
+fn package_name(item: &str) -> String {
+    WithEntry::parse(item).map_or_else(|_| item.to_string(), |entry| entry.name)
+}
+
+fn package_names(items: &[String]) -> HashSet<String> {
+    items.iter().map(|item| package_name(item)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    // houdini depends on usd and python, usd depends on python and tbb
+    fn resolver() -> MapResolver {
+        MapResolver::new()
+            .with_dependencies("houdini", vec!["usd", "python"])
+            .with_dependencies("usd", vec!["python", "tbb"])
+    }
+
+    #[test]
+    fn dependencies_follow_their_own_dependencies() {
+        let resolver = resolver();
+        assert_eq!(
+            transitive_dependencies(&resolver, "houdini"),
+            vec!["python", "tbb", "usd"]
+        );
+        assert!(transitive_dependencies(&resolver, "nuke").is_empty());
+    }
+
+    #[test]
+    fn cycles_are_tolerated() {
+        let resolver = MapResolver::new()
+            .with_dependencies("a", vec!["b"])
+            .with_dependencies("b", vec!["c"])
+            .with_dependencies("c", vec!["a"]);
+        assert_eq!(transitive_dependencies(&resolver, "a"), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn missing_dependencies_ignore_the_versions_of_present_items() {
+        let resolver = resolver();
+        assert_eq!(
+            missing_dependencies(&resolver, "houdini-18.0.348", &items(&["python-3.7"])),
+            vec!["tbb", "usd"]
+        );
+        assert!(
+            missing_dependencies(&resolver, "houdini", &items(&["tbb", "usd>=21", "python"]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn resolve_inserts_missing_dependencies_ahead_of_their_dependents() {
+        let resolver = resolver();
+        // dependencies already present are left where they are
+        assert_eq!(
+            resolve(&resolver, &items(&["nuke", "houdini-18.0.348", "usd"])),
+            vec!["nuke", "python", "tbb", "houdini-18.0.348", "usd"]
+        );
+        assert_eq!(
+            resolve(&resolver, &items(&["tbb-2020", "houdini"])),
+            vec!["tbb-2020", "python", "usd", "houdini"]
+        );
+    }
+
+    #[test]
+    fn implied_items_name_the_items_implying_them() {
+        let resolver = resolver();
+        let implied = implied_items(&resolver, &items(&["python-3.7", "usd", "houdini", "nuke"]));
+        assert_eq!(implied.get("python-3.7"), Some(&items(&["usd", "houdini"])));
+        assert_eq!(implied.get("usd"), Some(&items(&["houdini"])));
+        assert_eq!(implied.get("houdini"), None);
+        assert_eq!(implied.get("nuke"), None);
+    }
+}
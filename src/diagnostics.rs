@@ -0,0 +1,355 @@
+//! Validation of the items of a WithsList.
+//!
+//! Validators inspect the items as a whole, reporting diagnostics against
+//! individual items, such as a pair of conflicting packages or a package
+//! which is unknown. The WithsList renders each item's diagnostics as an
+//! icon and tooltip.
+use crate::with_entry::WithEntry;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The severity of a Diagnostic, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Retrieve the name of the severity, as used by stylesheets
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A message about an item
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// New up a Diagnostic
+    ///
+    /// # Arguments
+    /// * `severity` - The Severity
+    /// * `message` - The message, as a &str or String
+    ///
+    /// # Returns
+    /// * Diagnostic instance
+    pub fn new<M>(severity: Severity, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            severity,
+            message: message.into(),
+        }
+    }
+
+    /// New up an error Diagnostic
+    ///
+    /// # Arguments
+    /// * `message` - The message, as a &str or String
+    ///
+    /// # Returns
+    /// * Diagnostic instance
+    pub fn error<M: Into<String>>(message: M) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    /// New up a warning Diagnostic
+    ///
+    /// # Arguments
+    /// * `message` - The message, as a &str or String
+    ///
+    /// # Returns
+    /// * Diagnostic instance
+    pub fn warning<M: Into<String>>(message: M) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    /// New up an info Diagnostic
+    ///
+    /// # Arguments
+    /// * `message` - The message, as a &str or String
+    ///
+    /// # Returns
+    /// * Diagnostic instance
+    pub fn info<M: Into<String>>(message: M) -> Self {
+        Self::new(Severity::Info, message)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// A Diagnostic reported against an item of the list
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ItemDiagnostic {
+    /// The row of the item
+    pub row: usize,
+    /// The item
+    pub item: String,
+    pub diagnostic: Diagnostic,
+}
+
+/// Inspects the items of the list, reporting diagnostics against them.
+/// Validators of a WithsList inspect its WithEntries.
+pub trait Validator<T = WithEntry> {
+    /// Validate the entries
+    ///
+    /// # Arguments
+    /// * `entries` - The entries of the list, in order
+    ///
+    /// # Returns
+    /// * Vector of the row of an entry and a Diagnostic about it
+    fn validate(&self, entries: &[T]) -> Vec<(usize, Diagnostic)>;
+}
+
+/// Run each of the validators over the entries
+///
+/// # Arguments
+/// * `validators` - The Validators
+/// * `entries` - The entries of the list, in order
+///
+/// # Returns
+/// * Vector of ItemDiagnostics, ordered by row
+pub fn validate_all<T: fmt::Display>(
+    validators: &[Box<dyn Validator<T>>],
+    entries: &[T],
+) -> Vec<ItemDiagnostic> {
+    let mut diagnostics = validators
+        .iter()
+        .flat_map(|validator| validator.validate(entries))
+        .filter(|(row, _)| *row < entries.len())
+        .map(|(row, diagnostic)| ItemDiagnostic {
+            row,
+            item: entries[row].to_string(),
+            diagnostic,
+        })
+        .collect::<Vec<_>>();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.row);
+    diagnostics
+}
+
+/// Determine whether any of the diagnostics is an error, which blocks the
+/// list from being saved when it is configured to do so
+///
+/// # Arguments
+/// * `diagnostics` - The ItemDiagnostics of the list
+///
+/// # Returns
+/// * bool
+pub fn has_errors(diagnostics: &[ItemDiagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|d| d.diagnostic.severity == Severity::Error)
+}
+
+/// Reports an error against each package which appears along with a
+/// package it conflicts with
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConflictValidator {
+    conflicts: Vec<(String, String)>,
+}
+
+impl ConflictValidator {
+    /// New up an empty ConflictValidator
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * ConflictValidator instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that two packages conflict
+    ///
+    /// # Arguments
+    /// * `first` - A package name
+    /// * `second` - The package name conflicting with first
+    ///
+    /// # Returns
+    /// * The ConflictValidator
+    pub fn with_conflict(mut self, first: &str, second: &str) -> Self {
+        self.conflicts.push((first.to_string(), second.to_string()));
+        self
+    }
+}
+
+impl Validator for ConflictValidator {
+    fn validate(&self, entries: &[WithEntry]) -> Vec<(usize, Diagnostic)> {
+        let position = |name: &str| entries.iter().position(|entry| entry.name == name);
+        let mut diagnostics = Vec::new();
+        for (first, second) in &self.conflicts {
+            if let (Some(first_row), Some(second_row)) = (position(first), position(second)) {
+                diagnostics.push((
+                    first_row,
+                    Diagnostic::error(format!("conflicts with {}", second)),
+                ));
+                diagnostics.push((
+                    second_row,
+                    Diagnostic::error(format!("conflicts with {}", first)),
+                ));
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Reports a warning against each package which is not one of the known
+/// packages
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KnownPackagesValidator {
+    packages: HashSet<String>,
+}
+
+impl KnownPackagesValidator {
+    /// New up a KnownPackagesValidator
+    ///
+    /// # Arguments
+    /// * `packages` - Vector of the known package names
+    ///
+    /// # Returns
+    /// * KnownPackagesValidator instance
+    pub fn new<I>(packages: Vec<I>) -> Self
+    where
+        I: Into<String>,
+    {
+        Self {
+            packages: packages.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Validator for KnownPackagesValidator {
+    fn validate(&self, entries: &[WithEntry]) -> Vec<(usize, Diagnostic)> {
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.packages.contains(&entry.name))
+            .map(|(row, entry)| {
+                (
+                    row,
+                    Diagnostic::warning(format!("{} is not a known package", entry.name)),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(items: &[&str]) -> Vec<WithEntry> {
+        items
+            .iter()
+            .map(|item| WithEntry::parse(item).expect("valid with"))
+            .collect()
+    }
+
+    fn validators() -> Vec<Box<dyn Validator>> {
+        vec![
+            Box::new(KnownPackagesValidator::new(vec!["maya", "houdini", "nuke"])),
+            Box::new(ConflictValidator::new().with_conflict("maya", "houdini")),
+        ]
+    }
+
+    #[test]
+    fn severities_are_ordered_from_least_to_most_severe() {
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+        let worst = vec![Severity::Warning, Severity::Error, Severity::Info]
+            .into_iter()
+            .max();
+        assert_eq!(worst, Some(Severity::Error));
+        assert_eq!(
+            Diagnostic::warning("not known").to_string(),
+            "warning: not known"
+        );
+    }
+
+    #[test]
+    fn diagnostics_of_all_validators_are_gathered_by_row() {
+        let diagnostics = validate_all(
+            &validators(),
+            &entries(&["houdini-18.0.348", "katana", "maya>=2020"]),
+        );
+        let rows = diagnostics
+            .iter()
+            .map(|d| (d.row, d.item.as_str(), d.diagnostic.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    0,
+                    "houdini-18.0.348",
+                    "error: conflicts with maya".to_string()
+                ),
+                (
+                    1,
+                    "katana",
+                    "warning: katana is not a known package".to_string()
+                ),
+                (2, "maya>=2020", "error: conflicts with houdini".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diagnostics_for_rows_beyond_the_entries_are_dropped() {
+        struct Stale;
+        impl Validator for Stale {
+            fn validate(&self, _: &[WithEntry]) -> Vec<(usize, Diagnostic)> {
+                vec![
+                    (1, Diagnostic::info("stale")),
+                    (0, Diagnostic::info("kept")),
+                ]
+            }
+        }
+        let validators: Vec<Box<dyn Validator>> = vec![Box::new(Stale)];
+        let diagnostics = validate_all(&validators, &entries(&["maya"]));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].diagnostic.message, "kept");
+    }
+
+    #[test]
+    fn only_errors_block_saving() {
+        let validators = validators();
+        assert!(!has_errors(&validate_all(
+            &validators,
+            &entries(&["maya", "katana"])
+        )));
+        assert!(has_errors(&validate_all(
+            &validators,
+            &entries(&["maya", "houdini"])
+        )));
+        assert!(!has_errors(&[]));
+    }
+}
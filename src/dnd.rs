@@ -0,0 +1,287 @@
+//! Drag and drop of withs between WithsLists, and from other applications.
+//!
+//! Withs dragged between WithsLists travel as the items of the qt model,
+//! which carry each with's key, and so preserve the order and versions of
+//! the withs. The view inserts the dropped items itself, and the WithsList
+//! reconciles them with its model once the drop has completed,
+//! rejecting any which are invalid, unknown or duplicated. Holding the copy
+//! modifier (eg Ctrl) while dropping copies the withs, otherwise they are
+//! moved.
+//!
+//! Text dropped from other applications, such as a terminal, holds withs as
+//! per a paste. Files dropped from a file browser arrive as a uri list,
+//! whose paths are turned into package names by a PathMapper. As the qt
+//! model cannot be subclassed through the bindings, it accepts only its own
+//! items, so text and files are dropped onto the combobox rather than the
+//! view.
+use crate::clipboard::{split_entries, PasteRejection};
+use crate::list_item::ListItem;
+use crate::withs_model::ItemModel;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Maps the path of a dropped file to a package name, returning None if
+/// the path is not a package
+pub type PathMapper = Box<dyn Fn(&Path) -> Option<String>>;
+
+/// The default PathMapper, which takes the final component of the path,
+/// less any extension, as the package name (eg `/packages/houdini` or
+/// `/manifests/houdini.yaml` become `houdini`)
+///
+/// # Arguments
+/// * `path` - The path of the dropped file
+///
+/// # Returns
+/// * Some(package name), or None if the path has no final component
+pub fn package_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .map(str::to_string)
+}
+
+/// Determine whether the dropped text is a uri list, as dropped by a file
+/// browser
+///
+/// # Arguments
+/// * `text` - The dropped text
+///
+/// # Returns
+/// * bool
+pub fn is_uri_list(text: &str) -> bool {
+    uri_lines(text)
+        .next()
+        .is_some_and(|line| line.starts_with("file:"))
+}
+
+/// Retrieve the local path referred to by a `file` uri, decoding any
+/// percent encoded characters
+///
+/// # Arguments
+/// * `uri` - The uri (eg `file:///packages/houdini`)
+///
+/// # Returns
+/// * Some(path), or None if the uri does not refer to a local file
+pub fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file:")?;
+    // the host, if any, precedes the path
+    let path = match rest.strip_prefix("//") {
+        Some(authority) => &authority[authority.find('/')?..],
+        None => rest,
+    };
+    percent_decode(path).map(PathBuf::from)
+}
+
+/// Retrieve the entries held by dropped text. A uri list has each of its
+/// paths mapped to a package name, while other text is split into entries
+/// as per a paste. Uris which cannot be mapped are retained, so that they
+/// are reported as invalid.
+///
+/// # Arguments
+/// * `text` - The dropped text
+/// * `mapper` - Maps the path of each dropped file to a package name
+///
+/// # Returns
+/// * Vector of entries, which may or may not be valid
+pub fn dropped_entries(text: &str, mapper: &dyn Fn(&Path) -> Option<String>) -> Vec<String> {
+    if !is_uri_list(text) {
+        return split_entries(text);
+    }
+    uri_lines(text)
+        .map(|uri| {
+            path_from_uri(uri)
+                .and_then(|path| mapper(&path))
+                .unwrap_or_else(|| uri.to_string())
+        })
+        .collect()
+}
+
+/// The outcome of reconciling the items of the list with those of the view
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct Reconciled {
+    /// The items to keep, in the order of the view
+    pub(crate) items: Vec<String>,
+    /// The dropped items which were rejected, along with the reason
+    pub(crate) rejected: Vec<(String, PasteRejection)>,
+}
+
+/// Reconcile the items of the list with the keys of the view, after the
+/// view has moved, dropped or removed rows. Keys which are not among the
+/// items were dropped, and are validated as per a paste.
+///
+/// # Arguments
+/// * `model` - The ItemModel of the list
+/// * `keys` - The keys of the view's rows, in order
+/// * `is_candidate` - Determines whether a package name is known
+///
+/// # Returns
+/// * Reconciled
+pub(crate) fn reconcile<T, F>(model: &ItemModel<T>, keys: &[String], is_candidate: F) -> Reconciled
+where
+    T: ListItem,
+    F: Fn(&str) -> bool,
+{
+    let items = model.all_items();
+    let mut remaining = HashMap::<&str, usize>::new();
+    for item in &items {
+        *remaining.entry(item.as_str()).or_default() += 1;
+    }
+    // dropped entries are checked for duplicates against all of the items
+    let mut seen = model.clone();
+    let mut reconciled = Reconciled::default();
+    for key in keys {
+        if let Some(count) = remaining.get_mut(key.as_str()).filter(|count| **count > 0) {
+            *count -= 1;
+            reconciled.items.push(key.clone());
+            continue;
+        }
+        match T::parse_item(key) {
+            Err(e) => reconciled
+                .rejected
+                .push((key.clone(), PasteRejection::Invalid(e.to_string()))),
+            Ok(entry) if !is_candidate(entry.key()) => reconciled
+                .rejected
+                .push((key.clone(), PasteRejection::Unknown)),
+            Ok(entry) if seen.insert_entry(seen.len(), entry.clone()).is_err() => reconciled
+                .rejected
+                .push((key.clone(), PasteRejection::Duplicate)),
+            Ok(entry) => reconciled.items.push(entry.to_string()),
+        }
+    }
+    reconciled
+}
+
+// the lines of a uri list, less blanks and comments
+fn uri_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+// decode the percent encoded characters of a uri's path
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+        let hex = [iter.next()?, iter.next()?];
+        let hex = std::str::from_utf8(&hex).ok()?;
+        bytes.push(u8::from_str_radix(hex, 16).ok()?);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::{decode_withs, encode_withs};
+    use crate::with_entry::WithEntry;
+    use crate::withs_model::WithsModel;
+
+    fn no_packages(_: &Path) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn uris_are_decoded_to_local_paths() {
+        assert_eq!(
+            path_from_uri("file:///packages/houdini"),
+            Some(PathBuf::from("/packages/houdini"))
+        );
+        assert_eq!(
+            path_from_uri("file://studio/packages/my%20maya"),
+            Some(PathBuf::from("/packages/my maya"))
+        );
+        assert_eq!(
+            path_from_uri("file:/packages/nuke"),
+            Some(PathBuf::from("/packages/nuke"))
+        );
+        assert_eq!(path_from_uri("http://packages/nuke"), None);
+        assert_eq!(path_from_uri("file:///packages/%zz"), None);
+    }
+
+    #[test]
+    fn uri_lists_are_mapped_to_package_names() {
+        let text = "# dropped by the file browser\r\nfile:///packages/houdini\nfile:///manifests/maya.yaml\n";
+        assert!(is_uri_list(text));
+        assert_eq!(
+            dropped_entries(text, &package_from_path),
+            vec!["houdini", "maya"]
+        );
+        // uris which cannot be mapped are kept, so that they are reported
+        assert_eq!(
+            dropped_entries("file:///packages/houdini", &no_packages),
+            vec!["file:///packages/houdini"]
+        );
+    }
+
+    #[test]
+    fn other_text_is_dropped_as_per_a_paste() {
+        let text = "houdini-18.0.348, maya >= 2020";
+        assert!(!is_uri_list(text));
+        assert_eq!(
+            dropped_entries(text, &package_from_path),
+            vec!["houdini-18.0.348", "maya>=2020"]
+        );
+    }
+
+    #[test]
+    fn dragged_withs_survive_the_payload() {
+        let entries = vec![
+            (1, WithEntry::parse("nuke<12").expect("valid with")),
+            (
+                0,
+                WithEntry::parse("#~houdini-18.0.348").expect("valid with"),
+            ),
+        ];
+        assert_eq!(
+            decode_withs(&encode_withs(&entries)),
+            Some(vec![
+                "nuke<12".to_string(),
+                "#~houdini-18.0.348".to_string()
+            ])
+        );
+        assert_eq!(decode_withs("not json"), None);
+        assert_eq!(decode_withs(r#"{"version": 1}"#), None);
+        // documents from a later version of the format are refused
+        assert_eq!(
+            decode_withs(r#"{"version": 99, "items": [{"item": "maya"}]}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn reconcile_keeps_the_order_of_the_view_and_rejects_bad_drops() {
+        let model = WithsModel::from_items(vec!["maya", "#~nuke"]).expect("valid withs");
+        let keys = ["#~nuke", "katana", "maya", "maya", "mari>=", "unknown"]
+            .iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        let reconciled = reconcile(&model, &keys, |name| name != "unknown");
+        assert_eq!(reconciled.items, vec!["#~nuke", "katana", "maya"]);
+        assert_eq!(reconciled.rejected.len(), 3);
+        assert_eq!(
+            reconciled.rejected[0],
+            ("maya".to_string(), PasteRejection::Duplicate)
+        );
+        assert!(matches!(
+            reconciled.rejected[1],
+            (_, PasteRejection::Invalid(_))
+        ));
+        assert_eq!(
+            reconciled.rejected[2],
+            ("unknown".to_string(), PasteRejection::Unknown)
+        );
+    }
+
+    #[test]
+    fn reconcile_drops_rows_removed_from_the_view() {
+        let model = WithsModel::from_items(vec!["maya", "nuke"]).expect("valid withs");
+        let reconciled = reconcile(&model, &["nuke".to_string()], |_| true);
+        assert_eq!(reconciled.items, vec!["nuke"]);
+        assert!(reconciled.rejected.is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! Filtering of the withs displayed by the WithsList.
+//!
+//! Each of the FilterMatch strategies is translated into a regular
+//! expression, which the WithsList hands to the proxy model sitting between
+//! its model and its view. The expression is matched against the text of
+//! each with, without the prefix of a disabled with, so that disabled withs
+//! are filtered alike.
+
+/// The strategy used to match the filter text against the withs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterMatch {
+    /// Match withs containing the text
+    #[default]
+    Substring,
+    /// Match withs starting with the text
+    Prefix,
+    /// Match withs against a glob pattern, supporting `*`, `?` and `[...]`
+    Glob,
+    /// Match withs against a regular expression
+    Regex,
+}
+
+impl FilterMatch {
+    /// Translate the filter text into a regular expression implementing
+    /// the match strategy.
+    ///
+    /// # Arguments
+    /// * `text` - The filter text entered by the user
+    ///
+    /// # Returns
+    /// * The regular expression as a String
+    pub fn to_regex(&self, text: &str) -> String {
+        match self {
+            Self::Substring => escape(text),
+            Self::Prefix => format!("^{}", escape(text)),
+            Self::Glob => format!("^{}$", glob_to_regex(text)),
+            Self::Regex => text.to_string(),
+        }
+    }
+}
+
+// escape the characters which carry meaning within a regular expression
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// translate a glob into the equivalent unanchored regular expression
+fn glob_to_regex(glob: &str) -> String {
+    let chars = glob.chars().collect::<Vec<_>>();
+    let mut regex = String::with_capacity(glob.len() * 2);
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            // a character class is copied verbatim, provided that it is
+            // terminated. Otherwise the bracket is treated literally
+            '[' => match chars[idx + 1..].iter().position(|c| *c == ']') {
+                Some(len) => {
+                    let class = chars[idx + 1..idx + 1 + len].iter().collect::<String>();
+                    regex.push('[');
+                    // globs negate a class with a leading !
+                    let class = match class.strip_prefix('!') {
+                        Some(negated) => format!("^{}", negated),
+                        None => class,
+                    };
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    idx += len + 1;
+                }
+                None => regex.push_str("\\["),
+            },
+            c => regex.push_str(&escape(&c.to_string())),
+        }
+        idx += 1;
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_and_prefix_escape_the_text() {
+        assert_eq!(FilterMatch::Substring.to_regex("maya"), "maya");
+        assert_eq!(FilterMatch::Substring.to_regex("a.b+c"), "a\\.b\\+c");
+        assert_eq!(FilterMatch::Prefix.to_regex("hou"), "^hou");
+        assert_eq!(FilterMatch::Prefix.to_regex("nuke[12]"), "^nuke\\[12\\]");
+        assert_eq!(FilterMatch::default(), FilterMatch::Substring);
+    }
+
+    #[test]
+    fn globs_are_anchored_and_translated() {
+        assert_eq!(FilterMatch::Glob.to_regex("hou*"), "^hou.*$");
+        assert_eq!(FilterMatch::Glob.to_regex("maya-20??"), "^maya-20..$");
+        assert_eq!(
+            FilterMatch::Glob.to_regex("houdini-18.0.*"),
+            "^houdini-18\\.0\\..*$"
+        );
+    }
+
+    #[test]
+    fn glob_classes_are_copied_and_may_be_negated() {
+        assert_eq!(FilterMatch::Glob.to_regex("nuke[0-9]"), "^nuke[0-9]$");
+        assert_eq!(FilterMatch::Glob.to_regex("[!m]*"), "^[^m].*$");
+        assert_eq!(FilterMatch::Glob.to_regex("a[\\]"), "^a[\\\\]$");
+        // an unterminated class is matched literally
+        assert_eq!(FilterMatch::Glob.to_regex("maya[2"), "^maya\\[2$");
+    }
+
+    #[test]
+    fn regexes_are_passed_through() {
+        assert_eq!(
+            FilterMatch::Regex.to_regex("^(maya|nuke)-\\d+$"),
+            "^(maya|nuke)-\\d+$"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Fuzzy matching of the text entered by the user against candidate withs.
+//!
+//! The characters of the pattern must appear in the candidate in order,
+//! though not necessarily contiguously (eg `hdp` matches `houdinipipeline`).
+//! Matches are scored so that contiguous runs, and matches at the start of
+//! words, rank ahead of scattered matches.
+
+// score awarded for each matched character
+const MATCH_SCORE: i64 = 16;
+// bonus for a match immediately following the previous match
+const CONSECUTIVE_BONUS: i64 = 16;
+// bonus for a match at the start of the candidate or of a word within it
+const WORD_START_BONUS: i64 = 24;
+// penalty for each unmatched character between two matches
+const GAP_PENALTY: i64 = 2;
+// penalty for each unmatched character before the first match
+const LEADING_GAP_PENALTY: i64 = 1;
+// the leading gap penalty is capped so that long names are not buried
+const MAX_LEADING_GAP_PENALTY: i64 = 6;
+
+/// A candidate which matches the pattern, along with its score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// The candidate which matched
+    pub candidate: String,
+    /// The score of the match. Higher is better
+    pub score: i64,
+    /// The character (not byte) indices of the matched characters
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// Retrieve the candidate as rich text, with the matched characters
+    /// in bold
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String of html
+    pub fn highlighted(&self) -> String {
+        highlight(&self.candidate, &self.positions)
+    }
+}
+
+/// Match the pattern against a candidate, ignoring case.
+///
+/// # Arguments
+/// * `pattern` - The text entered by the user
+/// * `candidate` - The text to match against
+///
+/// # Returns
+/// * Some(FuzzyMatch) if each character of the pattern appears in the candidate,
+///   in order, or None
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let pattern = pattern.chars().map(lower_case).collect::<Vec<_>>();
+    let chars = candidate.chars().collect::<Vec<_>>();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            candidate: candidate.to_string(),
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if pattern.len() > chars.len() {
+        return None;
+    }
+    let lower = chars.iter().copied().map(lower_case).collect::<Vec<_>>();
+
+    // scores[i][j] holds the best score of matching pattern[..=i] with
+    // pattern[i] matched at chars[j]. parents[i][j] holds the position of
+    // pattern[i - 1] for that score
+    let mut scores = vec![vec![None; chars.len()]; pattern.len()];
+    let mut parents = vec![vec![0; chars.len()]; pattern.len()];
+    for (j, c) in lower.iter().enumerate() {
+        if *c == pattern[0] {
+            let leading = (j as i64 * LEADING_GAP_PENALTY).min(MAX_LEADING_GAP_PENALTY);
+            scores[0][j] = Some(MATCH_SCORE + bonus(&chars, j) - leading);
+        }
+    }
+    for i in 1..pattern.len() {
+        // the best score, and its position, among the earlier matches of
+        // pattern[i - 1], less the gap penalty up to the current position
+        let mut best: Option<(i64, usize)> = None;
+        for j in i..chars.len() {
+            let consecutive = scores[i - 1][j - 1].map(|s| (s + CONSECUTIVE_BONUS, j - 1));
+            best = best.map(|(s, k)| (s - GAP_PENALTY, k));
+            if lower[j] == pattern[i] {
+                let prev = match (consecutive, best) {
+                    (Some(c), Some(b)) if b.0 > c.0 => Some(b),
+                    (Some(c), _) => Some(c),
+                    (None, b) => b,
+                };
+                if let Some((score, k)) = prev {
+                    scores[i][j] = Some(score + MATCH_SCORE + bonus(&chars, j));
+                    parents[i][j] = k;
+                }
+            }
+            // pattern[i - 1] at j - 1 may precede a later, non consecutive
+            // match. The gap penalty is applied as we move past each position
+            if let Some(s) = scores[i - 1][j - 1] {
+                if best.is_none_or(|(b, _)| s > b) {
+                    best = Some((s, j - 1));
+                }
+            }
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut pos, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (j, s)))
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))?;
+    let mut positions = vec![pos; pattern.len()];
+    for i in (1..pattern.len()).rev() {
+        pos = parents[i][pos];
+        positions[i - 1] = pos;
+    }
+    Some(FuzzyMatch {
+        candidate: candidate.to_string(),
+        score,
+        positions,
+    })
+}
+
+/// Match the pattern against each of the candidates, returning the matches
+/// ranked best first. Ties are broken by favoring shorter candidates, and
+/// then alphabetically.
+///
+/// # Arguments
+/// * `pattern` - The text entered by the user
+/// * `candidates` - The candidates to match against
+///
+/// # Returns
+/// * Vector of FuzzyMatch, best first
+pub fn fuzzy_rank<I>(pattern: &str, candidates: I) -> Vec<FuzzyMatch>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|candidate| fuzzy_match(pattern, candidate.as_ref()))
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.candidate.len().cmp(&b.candidate.len()))
+            .then(a.candidate.cmp(&b.candidate))
+    });
+    matches
+}
+
+/// Render the candidate as rich text, with the characters at the supplied
+/// positions in bold
+///
+/// # Arguments
+/// * `candidate` - The text to render
+/// * `positions` - The character indices to embolden
+///
+/// # Returns
+/// * String of html
+pub fn highlight(candidate: &str, positions: &[usize]) -> String {
+    let mut html = String::with_capacity(candidate.len() * 2);
+    let mut bold = false;
+    for (idx, c) in candidate.chars().enumerate() {
+        let matched = positions.contains(&idx);
+        if matched != bold {
+            html.push_str(if matched { "<b>" } else { "</b>" });
+            bold = matched;
+        }
+        match c {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            c => html.push(c),
+        }
+    }
+    if bold {
+        html.push_str("</b>");
+    }
+    html
+}
+
+// the bonus for matching the character at idx, which is awarded at the
+// start of a word, whether delimited by punctuation or by camel case
+fn bonus(chars: &[char], idx: usize) -> i64 {
+    if idx == 0 {
+        return WORD_START_BONUS;
+    }
+    let (prev, cur) = (chars[idx - 1], chars[idx]);
+    if !prev.is_alphanumeric() || (prev.is_lowercase() && cur.is_uppercase()) {
+        WORD_START_BONUS
+    } else {
+        0
+    }
+}
+
+// compare characters one to one, ignoring case
+fn lower_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(pattern: &str, candidates: &[&str]) -> Vec<String> {
+        fuzzy_rank(pattern, candidates)
+            .into_iter()
+            .map(|m| m.candidate)
+            .collect()
+    }
+
+    #[test]
+    fn characters_must_appear_in_order() {
+        assert!(fuzzy_match("hdp", "houdinipipeline").is_some());
+        assert!(fuzzy_match("phd", "houdinipipeline").is_none());
+        assert!(fuzzy_match("mayaa", "maya").is_none());
+        assert!(fuzzy_match("x", "").is_none());
+        assert!(ranked("zz", &["maya", "nuke"]).is_empty());
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        let upper = fuzzy_match("MAYA", "maya").expect("match");
+        let lower = fuzzy_match("maya", "MAYA").expect("match");
+        assert_eq!(upper.positions, vec![0, 1, 2, 3]);
+        assert_eq!(upper.score, lower.score);
+    }
+
+    #[test]
+    fn an_empty_pattern_matches_everything() {
+        let m = fuzzy_match("", "maya").expect("match");
+        assert_eq!(m.score, 0);
+        assert!(m.positions.is_empty());
+        assert_eq!(ranked("", &["nuke", "maya"]), vec!["maya", "nuke"]);
+    }
+
+    #[test]
+    fn contiguous_and_word_start_matches_rank_first() {
+        assert_eq!(
+            ranked("pipe", &["apxixpxe", "houdinipipeline", "pipeline"]),
+            vec!["pipeline", "houdinipipeline", "apxixpxe"]
+        );
+        // word starts, whether by punctuation or camel case, beat the middle
+        // of a word
+        assert_eq!(
+            fuzzy_match("ht", "houdini_tools").expect("match").positions,
+            vec![0, 8]
+        );
+        assert_eq!(
+            fuzzy_match("ht", "houdiniTools").expect("match").positions,
+            vec![0, 7]
+        );
+    }
+
+    #[test]
+    fn ties_favor_shorter_then_alphabetical_candidates() {
+        assert_eq!(
+            ranked("nuke", &["nukex", "nuke", "nukea"]),
+            vec!["nuke", "nukea", "nukex"]
+        );
+    }
+
+    #[test]
+    fn highlight_emboldens_runs_of_matches_and_escapes_html() {
+        assert_eq!(highlight("maya", &[0, 1, 3]), "<b>ma</b>y<b>a</b>");
+        assert_eq!(highlight("a<b", &[1]), "a<b>&lt;</b>b");
+        assert_eq!(highlight("r&d", &[]), "r&amp;d");
+        let m = fuzzy_match("hdp", "houdini-pipe").expect("match");
+        assert_eq!(m.highlighted(), "<b>h</b>ou<b>d</b>ini-<b>p</b>ipe");
+    }
+
+    #[test]
+    fn positions_count_characters_rather_than_bytes() {
+        let m = fuzzy_match("ef", "éclair-effects").expect("match");
+        assert_eq!(m.positions, vec![7, 8]);
+        assert_eq!(m.highlighted(), "éclair-<b>ef</b>fects");
+    }
+}
@@ -0,0 +1,82 @@
+//! Handles to the items of a WithsList.
+//!
+//! A handle identifies an item by its row at the time it was found. As rows
+//! shift whenever the list changes, each handle records the generation of
+//! the list which produced it, and is refused once the list has moved on to
+//! a later generation. Find the item again to obtain a current handle.
+use std::error::Error;
+use std::fmt;
+
+/// Identifies an item of a WithsList, for as long as the list is unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ItemHandle {
+    item: String,
+    row: usize,
+    generation: u64,
+}
+
+impl ItemHandle {
+    pub(crate) fn new(item: String, row: usize, generation: u64) -> Self {
+        Self {
+            item,
+            row,
+            generation,
+        }
+    }
+
+    /// Retrieve the item, as it appeared when the handle was made
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn item(&self) -> &str {
+        &self.item
+    }
+
+    /// Retrieve the row of the item, as it was when the handle was made
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * usize
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Retrieve the generation of the list which produced the handle
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * u64
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+/// Error returned when a handle is used after the list has changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleHandle {
+    /// The item named by the handle
+    pub item: String,
+    /// The generation of the handle
+    pub generation: u64,
+    /// The current generation of the list
+    pub current: u64,
+}
+
+impl fmt::Display for StaleHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the handle to '{}' is stale: the list has changed since generation {} (now {})",
+            self.item, self.generation, self.current
+        )
+    }
+}
+
+impl Error for StaleHandle {}
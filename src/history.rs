@@ -0,0 +1,303 @@
+//! Undo / redo history.
+//!
+//! The History stores labelled edits. After each edit, a description of
+//! the edit, from which it may be undone and redone, is recorded along
+//! with a label (eg "Add houdini"). Rather than a snapshot of the state,
+//! the edit holds only what changed. Undoing an edit hands it back, to be
+//! reverted by the caller, and stashes it so that it may be redone.
+
+/// The default maximum number of edits retained by a History
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<T> {
+    label: String,
+    edit: T,
+}
+
+/// Undo and redo stacks of labelled edits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<T> {
+    undo: Vec<Entry<T>>,
+    redo: Vec<Entry<T>>,
+    limit: usize,
+    // the depth of the undo stack at which the state was last marked
+    // clean, or None if that state may no longer be reached
+    clean: Option<usize>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl<T> History<T> {
+    /// New up a History which retains at most `limit` edits
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum number of edits which may be undone
+    ///
+    /// # Returns
+    /// * History instance
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+            clean: Some(0),
+        }
+    }
+
+    /// Record an edit. This invalidates any edits which have been undone.
+    ///
+    /// # Arguments
+    /// * `label` - Description of the edit
+    /// * `edit` - The edit, describing how it may be undone and redone
+    ///
+    /// # Returns
+    /// * None
+    pub fn record<L>(&mut self, label: L, edit: T)
+    where
+        L: Into<String>,
+    {
+        // the clean state is lost along with the undone edits
+        if self.clean.is_some_and(|clean| clean > self.undo.len()) {
+            self.clean = None;
+        }
+        self.redo.clear();
+        if self.limit == 0 {
+            self.clean = None;
+            return;
+        }
+        if self.undo.len() == self.limit {
+            self.undo.remove(0);
+            self.clean = self.clean.and_then(|clean| clean.checked_sub(1));
+        }
+        self.undo.push(Entry {
+            label: label.into(),
+            edit,
+        });
+    }
+
+    /// Undo the last edit, moving it to the redo stack
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(edit) to be undone, or None if there is nothing to undo
+    pub fn undo(&mut self) -> Option<&T> {
+        let entry = self.undo.pop()?;
+        self.redo.push(entry);
+        self.redo.last().map(|e| &e.edit)
+    }
+
+    /// Redo the last undone edit, moving it back to the undo stack
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(edit) to be redone, or None if there is nothing to redo
+    pub fn redo(&mut self) -> Option<&T> {
+        let entry = self.redo.pop()?;
+        self.undo.push(entry);
+        self.undo.last().map(|e| &e.edit)
+    }
+
+    /// Determine whether there is an edit to undo
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Determine whether there is an edit to redo
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Retrieve the label of the edit which would be undone next
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(label), or None if there is nothing to undo
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|e| e.label.as_str())
+    }
+
+    /// Retrieve the label of the edit which would be redone next
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(label), or None if there is nothing to redo
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|e| e.label.as_str())
+    }
+
+    /// Retrieve the labels of the edits which may be undone, most recent first
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of labels
+    pub fn undo_labels(&self) -> Vec<&str> {
+        self.undo.iter().rev().map(|e| e.label.as_str()).collect()
+    }
+
+    /// Retrieve the labels of the edits which may be redone, most recent first
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of labels
+    pub fn redo_labels(&self) -> Vec<&str> {
+        self.redo.iter().rev().map(|e| e.label.as_str()).collect()
+    }
+
+    /// Mark the current state as clean, as when it has been saved
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn mark_clean(&mut self) {
+        self.clean = Some(self.undo.len());
+    }
+
+    /// Determine whether the edits have been undone or redone back to the
+    /// state last marked clean
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_clean(&self) -> bool {
+        self.clean == Some(self.undo.len())
+    }
+
+    /// Discard all of the recorded edits
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear(&mut self) {
+        self.clean = if self.is_clean() { Some(0) } else { None };
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a history of edits which each record the number they were given
+    fn recorded(edits: usize, limit: usize) -> History<usize> {
+        let mut history = History::new(limit);
+        for edit in 0..edits {
+            history.record(format!("Edit {}", edit), edit);
+        }
+        history
+    }
+
+    #[test]
+    fn nothing_to_undo_or_redo_when_empty() {
+        let mut history = History::<usize>::default();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo_label(), None);
+    }
+
+    #[test]
+    fn edits_move_between_the_undo_and_redo_stacks() {
+        let mut history = recorded(3, 10);
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.undo_labels(), vec!["Edit 0"]);
+        assert_eq!(history.redo_labels(), vec!["Edit 1", "Edit 2"]);
+        assert_eq!(history.redo(), Some(&1));
+        assert_eq!(history.undo_label(), Some("Edit 1"));
+        assert_eq!(history.redo_label(), Some("Edit 2"));
+    }
+
+    #[test]
+    fn recording_discards_the_undone_edits() {
+        let mut history = recorded(3, 10);
+        history.undo();
+        history.undo();
+        history.record("Edit 3", 3);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo_labels(), vec!["Edit 3", "Edit 0"]);
+    }
+
+    #[test]
+    fn the_oldest_edits_are_dropped_beyond_the_limit() {
+        let mut history = recorded(5, 3);
+        assert_eq!(history.undo_labels(), vec!["Edit 4", "Edit 3", "Edit 2"]);
+        assert_eq!(history.undo(), Some(&4));
+        assert_eq!(history.undo(), Some(&3));
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), None);
+        // a limit of zero records nothing
+        assert!(!recorded(2, 0).can_undo());
+    }
+
+    #[test]
+    fn the_clean_marker_follows_undo_and_redo() {
+        let mut history = recorded(2, 10);
+        assert!(!history.is_clean());
+        history.mark_clean();
+        assert!(history.is_clean());
+        history.record("Edit 2", 2);
+        assert!(!history.is_clean());
+        history.undo();
+        assert!(history.is_clean());
+        history.undo();
+        assert!(!history.is_clean());
+        history.redo();
+        assert!(history.is_clean());
+    }
+
+    #[test]
+    fn the_clean_marker_is_lost_with_the_edits_leading_to_it() {
+        let mut undone = recorded(2, 10);
+        undone.mark_clean();
+        undone.undo();
+        undone.record("Edit 2", 2);
+        undone.undo();
+        assert!(!undone.is_clean());
+
+        let mut dropped = recorded(0, 2);
+        dropped.mark_clean();
+        dropped.record("Edit 0", 0);
+        dropped.record("Edit 1", 1);
+        dropped.record("Edit 2", 2);
+        dropped.undo();
+        dropped.undo();
+        assert!(!dropped.is_clean());
+    }
+}
@@ -1,21 +1,75 @@
 use super::utility::qs;
+use crate::candidates::CandidateProvider;
+use crate::change::{
+    self, ChangeCallbacks, ContextActionCallback, Edit, SaveCallback, SharedSaveCallback,
+    WithsChange,
+};
+use crate::clipboard::{
+    self, split_entries, validate_entries, PasteReport, JSON_MIME_TYPE, WITHS_MIME_TYPE,
+};
+use crate::dependency::{self, DependencyResolver};
+use crate::diagnostics::{
+    has_errors, validate_all, Diagnostic, ItemDiagnostic, Severity, Validator,
+};
+use crate::dnd::{self, PathMapper};
+use crate::filter::FilterMatch;
+use crate::fuzzy::{fuzzy_match, fuzzy_rank, FuzzyMatch};
+use crate::handle::{ItemHandle, StaleHandle};
+use crate::history::History;
+use crate::list_item::ListItem;
+use crate::loader::{CandidateLoad, LoadEvent};
+use crate::persist::{self, PersistError, TextDocument, WithsFormat};
 use crate::toolbar::ItemListModeToolbar;
 use crate::traits::*;
 use crate::utility::load_stylesheet;
-use log;
-use qt_core::{q_item_selection_model::SelectionFlag, MatchFlag, QModelIndex, QString};
-use qt_gui::{QStandardItem, QStandardItemModel};
+use crate::with_entry::{version_completions, WithEntry};
+use crate::withs_model::{ItemModel, ItemModelError};
+use qt_core::{
+    q_item_selection_model::SelectionFlag, q_reg_exp::PatternSyntax, CaseSensitivity, CheckState,
+    ContextMenuPolicy, DropAction, ItemDataRole, QAbstractItemModel, QByteArray, QMimeData,
+    QModelIndex, QPoint, QRegExp, QSortFilterProxyModel, QString, QTimer, QVariant, TextFormat,
+    WidgetAttribute,
+};
+use qt_gui::{QGuiApplication, QIcon, QStandardItem, QStandardItemModel};
 use qt_widgets::{
     cpp_core::Ref as QRef,
     cpp_core::{CppBox, MutPtr},
     q_abstract_item_view::DragDropMode,
+    q_abstract_item_view::ScrollHint,
     q_abstract_item_view::SelectionMode,
     q_action::ActionEvent,
-    QAction, QComboBox, QFrame, QHBoxLayout, QLabel, QLayout, QListView, QPushButton, QToolBar,
-    QWidget,
+    q_completer::CompletionMode,
+    q_message_box::StandardButton,
+    q_style::StandardPixmap,
+    QAction, QComboBox, QCompleter, QFrame, QHBoxLayout, QLabel, QLayout, QListView, QMenu,
+    QMessageBox, QPushButton, QToolBar, QWidget,
 };
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
 //use rustqt_utils::{as_mut_ref, as_ref, enclose, enclose_all};
 
+// the number of completions which are rendered with their matches
+// highlighted. Each highlighted completion requires a widget, so the
+// remainder are displayed as plain text
+const MAX_HIGHLIGHTED_COMPLETIONS: usize = 50;
+// the interval, in milliseconds, at which candidates loading in the
+// background are collected
+const LOAD_POLL_INTERVAL_MS: i32 = 50;
+// the number of candidates requested per page when loading in the background
+const LOAD_BATCH_SIZE: usize = 500;
+// the maximum number of batches added to the combobox each interval, which
+// bounds the time spent on the gui thread
+const MAX_LOAD_BATCHES_PER_POLL: usize = 4;
+// the icon displayed by locked items which have no diagnostics, from the
+// application's registered resources
+const LOCK_ICON: &str = ":/images/lock.svg";
+
 //
 // ITEMLIST
 //
@@ -23,39 +77,86 @@ use qt_widgets::{
 /// to switch between reordering, deleting, and adding members.
 /// It stores the main components that are interesting to
 /// its clients, including the toolbar, the model, the view,
-/// the actual items backing data, and various slots
-pub(crate) struct InnerWithsList {
+/// the actual items backing data, and various slots.
+///
+/// The ItemModel is the source of truth for the list. The QStandardItemModel
+/// is kept in step with it and serves as the view adapter, rendering each
+/// row through the item's ListItem implementation.
+pub(crate) struct InnerItemList<T: ListItem> {
     main: MutPtr<QWidget>,
     mode_toolbar: ItemListModeToolbar,
     add_label: MutPtr<QLabel>,
+    loading_label: MutPtr<QLabel>,
+    load_timer: MutPtr<QTimer>,
     add_combobox: MutPtr<QComboBox>,
+    completer: MutPtr<QCompleter>,
+    completer_model: MutPtr<QStandardItemModel>,
     model: CppBox<QStandardItemModel>,
+    proxy: MutPtr<QSortFilterProxyModel>,
     view: MutPtr<QListView>,
     save_button: MutPtr<QPushButton>,
+    revert_button: MutPtr<QPushButton>,
+    data: RefCell<ItemModel<T>>,
+    // set while we are updating the qt model ourselves, so that the
+    // model signals do not attempt to sync back from the view
+    syncing: Cell<bool>,
+    callbacks: RefCell<ChangeCallbacks>,
+    save_callback: RefCell<Option<SharedSaveCallback>>,
+    history: RefCell<History<Edit>>,
+    // available versions of each package, used to complete versions
+    versions: RefCell<HashMap<String, Vec<String>>>,
+    completions: RefCell<Vec<FuzzyMatch>>,
+    filter_match: Cell<FilterMatch>,
+    filter_text: RefCell<String>,
+    // the text file last imported, which is only read by lists of withs
+    document: RefCell<Option<TextDocument>>,
+    // the candidates loaded from the candidate provider, if one is installed,
+    // which are offered in place of the combobox items
+    provided: RefCell<Option<Vec<String>>>,
+    provider_load: RefCell<Option<CandidateLoad>>,
+    load: RefCell<Option<CandidateLoad>>,
+    resolver: RefCell<Option<Box<dyn DependencyResolver>>>,
+    validators: RefCell<Vec<Box<dyn Validator<T>>>>,
+    diagnostics: RefCell<Vec<ItemDiagnostic>>,
+    block_save_on_errors: Cell<bool>,
+    sync_timer: MutPtr<QTimer>,
+    drag_drop: Cell<bool>,
+    path_mapper: RefCell<PathMapper>,
+    context_actions: RefCell<Vec<(String, ContextActionCallback)>>,
+    // incremented whenever the items change, invalidating ItemHandles
+    generation: Cell<u64>,
 }
 
-impl InnerWithsList {
-    /// New up an WithsList given a parent
+impl<T: ListItem> InnerItemList<T> {
+    /// New up an InnerItemList given a parent
     ///
     /// # Arguments
     /// * `parent` - MutPtr to the parent QWidget
     ///
     /// # Returns
-    /// * An WithsList instance
-    pub(crate) fn new(parent: MutPtr<QWidget>) -> InnerWithsList {
+    /// * An InnerItemList instance
+    pub(crate) fn new(parent: MutPtr<QWidget>) -> InnerItemList<T> {
         unsafe {
-            let parent = parent;
             let mut main_ptr = Self::setup_main_widget(&parent);
 
             let mut model = Self::setup_model();
 
             let mode_toolbar = ItemListModeToolbar::new(&mut main_ptr);
 
-            let (cblabel, cbox) = Self::setup_combobox("ItemCombo", &mut main_ptr);
+            let (cblabel, loading_label, cbox) = Self::setup_combobox("ItemCombo", &mut main_ptr);
+            let mut load_timer = QTimer::new_1a(main_ptr);
+            load_timer.set_interval(LOAD_POLL_INTERVAL_MS);
+            let (completer, completer_model) = Self::setup_completer(cbox);
+            // the view's edits are synced once any drag has completed
+            let mut sync_timer = QTimer::new_1a(main_ptr);
+            sync_timer.set_single_shot(true);
+            sync_timer.set_interval(0);
+
+            let proxy = Self::setup_proxy(model.as_mut_ptr(), main_ptr);
 
-            let listview_ptr = Self::setup_listview(model.as_mut_ptr(), &mut main_ptr.layout());
+            let listview_ptr = Self::setup_listview(proxy, &mut main_ptr.layout());
             //buttons
-            let save_button = Self::setup_button("Save", &mut main_ptr.layout());
+            let (revert_button, save_button) = Self::setup_buttons(&mut main_ptr);
             /*
              // shortcuts
             let key_seq = QKeySequence::from_int(Key::KeyReturn.to_int());
@@ -81,12 +182,41 @@ impl InnerWithsList {
             let f = Self {
                 main: main_ptr,
                 model,
+                proxy,
                 mode_toolbar,
                 add_label: cblabel,
+                loading_label,
+                load_timer: load_timer.into_ptr(),
                 add_combobox: cbox,
+                completer,
+                completer_model,
                 view: listview_ptr,
                 save_button,
+                revert_button,
+                data: RefCell::new(ItemModel::new()),
+                syncing: Cell::new(false),
+                callbacks: RefCell::new(ChangeCallbacks::default()),
+                save_callback: RefCell::new(None),
+                history: RefCell::new(History::default()),
+                versions: RefCell::new(HashMap::new()),
+                completions: RefCell::new(Vec::new()),
+                filter_match: Cell::new(FilterMatch::default()),
+                filter_text: RefCell::new(String::new()),
+                document: RefCell::new(None),
+                provided: RefCell::new(None),
+                provider_load: RefCell::new(None),
+                load: RefCell::new(None),
+                resolver: RefCell::new(None),
+                validators: RefCell::new(Vec::new()),
+                diagnostics: RefCell::new(Vec::new()),
+                block_save_on_errors: Cell::new(false),
+                sync_timer: sync_timer.into_ptr(),
+                drag_drop: Cell::new(false),
+                path_mapper: RefCell::new(Box::new(dnd::package_from_path)),
+                context_actions: RefCell::new(Vec::new()),
+                generation: Cell::new(0),
             };
+            f.update_buttons();
 
             f
         }
@@ -103,7 +233,7 @@ impl InnerWithsList {
         self.main
     }
 
-    /// Return a MutPtr to the with packages QListView
+    /// Return a MutPtr to the items' QListView
     ///
     /// # Arguments
     /// * None
@@ -126,7 +256,7 @@ impl InnerWithsList {
         self.mode_toolbar.toolbar()
     }
 
-    /// Returns a MutPtr to the combobox used to add and find with packages
+    /// Returns a MutPtr to the combobox used to add, find and filter items
     ///
     /// # Arguments
     /// * None
@@ -145,7 +275,20 @@ impl InnerWithsList {
     /// # Returns
     /// * MutPtr<QStandardItemModel>
     pub(crate) fn model(&self) -> MutPtr<QStandardItemModel> {
-        unsafe { self.view.model().dynamic_cast_mut() }
+        unsafe { self.proxy.source_model().dynamic_cast_mut() }
+    }
+
+    /// Returns a MutPtr to the proxy model which filters the items
+    /// displayed by the view
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr<QSortFilterProxyModel>
+    #[allow(dead_code)]
+    pub(crate) fn proxy(&self) -> MutPtr<QSortFilterProxyModel> {
+        self.proxy
     }
 
     /// Returns a MutPtr to the add/find combobox's label
@@ -171,6 +314,17 @@ impl InnerWithsList {
         self.save_button
     }
 
+    /// Returns a MutPtr to the revert button
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr<QPushButton>
+    pub(crate) fn revert_button(&self) -> MutPtr<QPushButton> {
+        self.revert_button
+    }
+
     #[allow(dead_code)]
     /// Determine if the find mode is active
     ///
@@ -190,11 +344,68 @@ impl InnerWithsList {
     ///
     /// # Returns
     /// * bool indicating whether or not the add mode is active
-    #[allow(dead_code)]
     pub(crate) fn is_add_active(&self) -> bool {
         self.mode_toolbar.is_add_active()
     }
 
+    /// Determine whether the filter mode is active
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether or not the filter mode is active
+    pub(crate) fn is_filter_active(&self) -> bool {
+        self.mode_toolbar.is_filter_active()
+    }
+
+    /// Set the strategy used to match the filter text against the items
+    ///
+    /// # Arguments
+    /// * `filter_match` - The FilterMatch strategy
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_filter_match(&self, filter_match: FilterMatch) {
+        self.filter_match.set(filter_match);
+        self.apply_filter();
+    }
+
+    /// Retrieve the strategy used to match the filter text against the items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * FilterMatch
+    pub(crate) fn filter_match(&self) -> FilterMatch {
+        self.filter_match.get()
+    }
+
+    /// Hide the items which do not match the supplied text. An empty
+    /// text shows all of the items.
+    ///
+    /// # Arguments
+    /// * `text` - The filter text
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_filter_text(&self, text: &str) {
+        self.filter_text.replace(text.to_string());
+        self.apply_filter();
+    }
+
+    /// Show all of the items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_filter(&self) {
+        self.set_filter_text("");
+    }
+
     #[allow(dead_code)]
     /// Clear the listview and its backng model
     ///
@@ -204,31 +415,38 @@ impl InnerWithsList {
     /// # Returns
     /// None
     pub(crate) fn clear(&self) {
-        unsafe {
-            let mut model = self.model();
-            model.clear();
-        }
+        let before = self.all_items();
+        self.data.borrow_mut().clear();
+        self.record("Clear", &before);
+        self.rebuild_view();
+        self.notify(WithsChange::Reset);
     }
 
     #[allow(dead_code)]
     /// Sets the contents to items, removing any pre-existing
-    /// items.
+    /// items. The items become the baseline against which edits
+    /// are measured. The list is left untouched if any of the items
+    /// is invalid.
     ///
     /// # Arguments
     /// * `items` - a Vector of &str or String
     ///
     /// # Returns
-    /// None
-    pub(crate) fn set_items<I>(&self, items: Vec<I>)
+    /// * Ok, or the first ItemModelError encountered
+    pub(crate) fn set_items<I>(&self, items: Vec<I>) -> Result<(), ItemModelError>
     where
         I: AsRef<str>,
     {
-        unsafe {
-            self.model().clear();
-            for item in items {
-                self.add_item(item.as_ref());
-            }
+        let before = self.all_items();
+        {
+            let mut data = self.data.borrow_mut();
+            data.set_items(items)?;
+            data.mark_clean();
         }
+        self.record("Set Items", &before);
+        self.rebuild_view();
+        self.notify(WithsChange::Reset);
+        Ok(())
     }
 
     /// Add an item to self.
@@ -237,226 +455,2351 @@ impl InnerWithsList {
     /// * iteem - The name of the item
     ///
     /// # Returns
-    /// * Noen
-    pub(crate) fn add_item_to(&self, item: &str) {
-        unsafe {
-            let mut si = QStandardItem::new();
-            si.set_text(&qs(item));
-            si.set_drop_enabled(false);
-            self.model().append_row_q_standard_item(si.into_ptr());
+    /// * Ok wrapping the row of the new item, or an ItemModelError
+    pub(crate) fn add_item_to(&self, item: &str) -> Result<usize, ItemModelError> {
+        let row = self.data.borrow().len();
+        self.insert_item(row, item)
+    }
+
+    /// Insert an item at the supplied row
+    ///
+    /// # Arguments
+    /// * `row` - The row to insert the item at
+    /// * `item` - The name of the item
+    ///
+    /// # Returns
+    /// * Ok wrapping the row of the new item, or an ItemModelError
+    pub(crate) fn insert_item(&self, row: usize, item: &str) -> Result<usize, ItemModelError> {
+        let before = self.all_items();
+        let row = self.data.borrow_mut().insert(row, item)?;
+        let entry = self.data.borrow().entries()[row].clone();
+        self.record(format!("Add {}", entry), &before);
+        self.sync_guard(|| unsafe {
+            self.model()
+                .insert_row_int_q_standard_item(row as i32, Self::new_item(&entry).into_ptr());
+        });
+        self.notify(WithsChange::Added {
+            item: entry.to_string(),
+            row,
+        });
+        Ok(row)
+    }
+
+    /// Insert several items, starting at the supplied row, as a single edit.
+    /// The list is left untouched if any of the items is invalid or
+    /// duplicated.
+    ///
+    /// # Arguments
+    /// * `row` - The row to insert the first item at
+    /// * `items` - The items to insert
+    ///
+    /// # Returns
+    /// * Ok wrapping the row of the first item, or an ItemModelError
+    pub(crate) fn insert_items(
+        &self,
+        row: usize,
+        items: &[String],
+    ) -> Result<usize, ItemModelError> {
+        if items.is_empty() {
+            return Ok(row.min(self.data.borrow().len()));
         }
+        let before = self.all_items();
+        let rows = self.data.borrow_mut().insert_items(row, items)?;
+        let first = rows.start;
+        let entries = self.data.borrow().entries()[rows.clone()].to_vec();
+        let label = match entries.as_slice() {
+            [entry] => format!("Add {}", entry),
+            _ => format!("Add {} items", entries.len()),
+        };
+        self.record(label, &before);
+        self.sync_guard(|| unsafe {
+            for (row, entry) in rows.clone().zip(&entries) {
+                self.model()
+                    .insert_row_int_q_standard_item(row as i32, Self::new_item(entry).into_ptr());
+            }
+        });
+        self.notify_all(
+            rows.zip(&entries)
+                .map(|(row, entry)| WithsChange::Added {
+                    item: entry.to_string(),
+                    row,
+                })
+                .collect(),
+        );
+        Ok(first)
     }
 
-    /// Retrieve a vector of Strings for items
+    /// Install the resolver consulted for the dependencies of the items
+    ///
+    /// # Arguments
+    /// * `resolver` - The DependencyResolver
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_dependency_resolver(&self, resolver: Box<dyn DependencyResolver>) {
+        self.resolver.replace(Some(resolver));
+        self.refresh_decorations();
+    }
+
+    /// Remove the dependency resolver
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * Vec<String>
-    pub(crate) fn items(&self) -> Vec<String> {
-        unsafe {
-            let sz = self.model.row_count_0a();
-            let mut rval = Vec::with_capacity(sz as usize);
-            for c in 0..sz {
-                let item = self.model.item_1a(c);
-                if item.is_null() {
-                    log::error!("item ptr is null. skipping");
-                    continue;
-                }
-                rval.push(item.text().to_std_string());
-            }
-            rval
-        }
+    /// * None
+    pub(crate) fn clear_dependency_resolver(&self) {
+        self.resolver.replace(None);
+        self.refresh_decorations();
     }
 
-    /// add an item to the pulldown
+    /// Retrieve the dependencies of the item which are absent from the list
     ///
     /// # Arguments
-    /// * The item to be added, as a &str or String
-    pub(crate) fn add_item<I>(&self, item: I)
-    where
-        I: AsRef<str>,
-    {
-        self.add_item_to(item.as_ref());
+    /// * `item` - The item
+    ///
+    /// # Returns
+    /// * Vector of package names, which is empty without a resolver
+    pub(crate) fn missing_dependencies(&self, item: &str) -> Vec<String> {
+        match self.resolver.borrow().as_ref() {
+            Some(resolver) => {
+                dependency::missing_dependencies(resolver.as_ref(), item, &self.items())
+            }
+            None => Vec::new(),
+        }
     }
 
-    /// add an item to the pulldown
+    /// Retrieve the items, expanded to include all of their dependencies
     ///
     /// # Arguments
-    /// * The item to be found, as a &MutPtr<QString>
-    #[allow(dead_code)]
-    pub(crate) fn find_item<'a>(&self, item: QRef<QString>) -> Option<MutPtr<QStandardItem>> {
-        Self::_find_item(item, &self.model())
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items, which are the items themselves without a resolver
+    pub(crate) fn resolved_items(&self) -> Vec<String> {
+        match self.resolver.borrow().as_ref() {
+            Some(resolver) => dependency::resolve(resolver.as_ref(), &self.items()),
+            None => self.items(),
+        }
     }
 
-    /// scroll to the provided item in the list
+    /// Retrieve the items which are dependencies of other items in the list
     ///
     /// # Arguments
-    /// * `item` - A Ref wrapped QString.
-    /// * `select1 - a boolean indicating whether the item should be selected as well as centered
-    /// in the view
-    #[allow(dead_code)]
-    pub(crate) fn scroll_to_item<'a>(&self, item: QRef<QString>, select_item: bool) -> bool {
-        Self::_scroll_to_item(item, &mut self.view(), &mut self.model(), select_item)
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items, in list order
+    pub(crate) fn implied_items(&self) -> Vec<String> {
+        let implied = self.implied_by();
+        self.items()
+            .into_iter()
+            .filter(|item| implied.contains_key(item))
+            .collect()
     }
 
-    /// Select the provided item given a Ref wrapped QModelIndex
+    /// Offer to insert the dependencies of the item which are missing from
+    /// the list, ahead of the item.
     ///
     /// # Arguments
-    /// * `item` - QModelIndex of the item we wish to select
+    /// * `item` - The item which was added
     ///
     /// # Returns
     /// * None
-    #[allow(dead_code)]
-    pub(crate) fn select_item(&self, item: QRef<QModelIndex>) {
-        unsafe {
-            Self::_select_item(item, &self.view);
+    pub(crate) fn offer_dependencies(&self, item: &str) {
+        let missing = self.missing_dependencies(item);
+        if missing.is_empty() {
+            return;
+        }
+        let row = match self.data.borrow().position(item) {
+            Some(row) => row,
+            None => return,
+        };
+        let answer = unsafe {
+            QMessageBox::question_q_widget2_q_string(
+                self.main,
+                &qs("Add Dependencies"),
+                &qs(format!(
+                    "{} depends upon:\n\n{}\n\nAdd them to the list?",
+                    item,
+                    missing.join("\n")
+                )),
+            )
+        };
+        if answer != StandardButton::Yes {
+            return;
+        }
+        if let Err(e) = self.insert_items(row, &missing) {
+            log::error!("unable to add dependencies of {}: {}", item, e);
         }
     }
 
-    #[allow(dead_code)]
-    /// Delete selected items from the list.
+    /// Remove the items at the supplied rows. This is the path through
+    /// which all removals are made, whether from the keyboard shortcuts or
+    /// programmatically. The rows may be supplied in any order. The list is
+    /// left untouched if any of the rows is out of range.
     ///
     /// # Arguments
-    /// None
+    /// * `rows` - The rows of the items to remove
     ///
     /// # Returns
-    /// None
-    pub(crate) fn delete_sel_items(&mut self) {
-        unsafe {
-            let selected = self.view.selection_model().selected_indexes();
-            if selected.length() == 0 {
-                return;
-            }
-            for c in 0..selected.length() {
-                self.view.model().remove_row_1a(c);
-            }
+    /// * Ok wrapping the removed items in row order, or an ItemModelError
+    pub(crate) fn remove_rows(&self, rows: &[usize]) -> Result<Vec<String>, ItemModelError> {
+        let before = self.all_items();
+        let removed = self.data.borrow_mut().remove_rows(rows)?;
+        if removed.is_empty() {
+            return Ok(Vec::new());
         }
+        let label = match removed.as_slice() {
+            [(_, entry)] => format!("Remove {}", entry),
+            _ => format!("Remove {} items", removed.len()),
+        };
+        self.record(label, &before);
+        // rows are removed from the bottom up, so that the remaining
+        // rows are unaffected by each removal
+        self.sync_guard(|| unsafe {
+            let mut model = self.model();
+            for (row, _) in &removed {
+                model.remove_row_1a(*row as i32);
+            }
+        });
+        let changes = removed
+            .iter()
+            .map(|(row, entry)| WithsChange::Removed {
+                item: entry.to_string(),
+                row: *row,
+            })
+            .collect::<Vec<_>>();
+        self.notify_all(changes);
+        Ok(removed
+            .into_iter()
+            .rev()
+            .map(|(_, entry)| entry.to_string())
+            .collect())
     }
 
-    #[allow(dead_code)]
-    /// Set comboboc items, replacing any extant items
+    /// Remove the supplied items from the list
     ///
     /// # Arguments
-    /// * `items` - Vector of items
+    /// * `items` - The names of the items, with or without version constraints
+    ///
+    /// # Returns
+    /// * Ok wrapping the removed items in row order, or NotFound if any of the
+    ///   items is not in the list, in which case nothing is removed
+    pub(crate) fn remove_items(&self, items: &[&str]) -> Result<Vec<String>, ItemModelError> {
+        let rows = {
+            let data = self.data.borrow();
+            items
+                .iter()
+                .map(|item| {
+                    data.position(item)
+                        .ok_or_else(|| ItemModelError::NotFound(item.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        self.remove_rows(&rows)
+    }
+
+    /// Add the entries held by the text, which may be separated by newlines,
+    /// commas or whitespace. Each entry is validated against the candidates,
+    /// as it is when entered in the combobox. The valid entries are inserted,
+    /// as a single edit, at the first selected row, or at the end of the list
+    /// if nothing is selected.
+    ///
+    /// # Arguments
+    /// * `text` - The text holding the entries
+    ///
+    /// # Returns
+    /// * PasteReport describing the entries which were added and rejected
+    pub(crate) fn paste_text(&self, text: &str) -> PasteReport {
+        self.add_entries(split_entries(text))
+    }
+
+    /// Add the entries held by text dropped onto the combobox. A uri list,
+    /// as dropped by a file browser, has its paths mapped to items
+    /// by the path mapper. Other text is added as per `paste_text`.
+    ///
+    /// # Arguments
+    /// * `text` - The dropped text
+    ///
+    /// # Returns
+    /// * PasteReport describing the entries which were added and rejected
+    pub(crate) fn drop_text(&self, text: &str) -> PasteReport {
+        let entries = dnd::dropped_entries(text, self.path_mapper.borrow().as_ref());
+        self.add_entries(entries)
+    }
+
+    /// Enable or disable drag and drop between ItemLists. When disabled,
+    /// items may only be moved within the list.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to enable drag and drop between lists
     ///
     /// # Returns
     /// * None
-    pub(crate) fn set_cb_items<'c, I>(&self, items: Vec<I>)
-    where
-        I: AsRef<str>,
-    {
+    pub(crate) fn set_drag_drop_enabled(&self, enabled: bool) {
         unsafe {
-            self.remove_cb_items();
-            self.add_combobox().add_item_q_string(&qs(""));
-            for item in items {
-                self.add_combobox().add_item_q_string(&qs(item.as_ref()));
+            let mut view = self.view;
+            if enabled {
+                view.set_drag_drop_mode(DragDropMode::DragDrop);
+            } else {
+                view.set_drag_drop_mode(DragDropMode::InternalMove);
             }
+            // dropped items are moved unless the copy modifier is held
+            view.set_default_drop_action(DropAction::MoveAction);
         }
+        self.drag_drop.set(enabled);
     }
 
-    #[allow(dead_code)]
-    /// Remove all items from the combobox
+    /// Determine whether drag and drop between ItemLists is enabled
     ///
     /// # Arguments
     /// * None
     ///
-    /// # Returns None
-    pub(crate) fn remove_cb_items(&self) {
-        unsafe {
-            self.add_combobox().clear();
-        }
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_drag_drop_enabled(&self) -> bool {
+        self.drag_drop.get()
     }
 
-    /// Change the max number of items displayed in the combobox's dropdown
-    /// list
+    /// Set the mapper which turns the paths of dropped files into items,
+    /// replacing the default, which uses the file name less any extension
     ///
     /// # Arguments
-    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    /// * `mapper` - The PathMapper
     ///
     /// # Returns
     /// * None
-    pub(crate) fn set_cb_max_visible_items(&self, max: i32) {
-        unsafe {
-            self.add_combobox().set_max_visible_items(max);
+    pub(crate) fn set_path_mapper(&self, mapper: PathMapper) {
+        self.path_mapper.replace(mapper);
+    }
+
+    // validate the entries, adding those which pass as a single edit
+    fn add_entries(&self, entries: Vec<String>) -> PasteReport {
+        let existing = self.data.borrow().clone();
+        let mut report = validate_entries(entries, &existing, |name| self.is_candidate(name));
+        if report.added.is_empty() {
+            return report;
+        }
+        let row = self
+            .selected_rows()
+            .first()
+            .copied()
+            .unwrap_or_else(|| self.data.borrow().len());
+        if let Err(e) = self.insert_items(row, &report.added) {
+            log::error!("unable to paste items: {}", e);
+            report.added.clear();
         }
+        report
     }
 
-    /// Given a path as a &str to a stylesheet, apply it to the components.
+    /// Add the entries held by the clipboard. Items copied from an ItemList
+    /// are preferred, falling back to the clipboard's text. See `paste_text`
     ///
     /// # Arguments
-    /// * `sheet` - Path to the qss stylesheet
+    /// * None
     ///
     /// # Returns
-    /// * None
-    pub(crate) fn set_stylesheet(&self, sheet: &str) {
-        load_stylesheet(sheet, self.main);
-    }
-
-    pub(crate) fn find_mode_action(&self) -> MutPtr<QAction> {
-        self.mode_toolbar.find_mode_action
+    /// * PasteReport describing the entries which were added and rejected
+    pub(crate) fn paste(&self) -> PasteReport {
+        let text = unsafe {
+            let mime_data = QGuiApplication::clipboard().mime_data_0a();
+            let withs = if mime_data.has_format(&qs(WITHS_MIME_TYPE)) {
+                let data = mime_data.data(&qs(WITHS_MIME_TYPE));
+                let bytes = std::slice::from_raw_parts(
+                    data.const_data() as *const u8,
+                    data.size() as usize,
+                );
+                std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(clipboard::decode_withs)
+            } else {
+                None
+            };
+            match withs {
+                Some(withs) => withs.join("\n"),
+                None => mime_data.text().to_std_string(),
+            }
+        };
+        self.paste_text(&text)
     }
 
-    pub(crate) fn add_mode_action(&self) -> MutPtr<QAction> {
-        self.mode_toolbar.add_mode_action
+    /// Place the selected entries on the clipboard, in row order, as plain
+    /// text, json, and the WITHS_MIME_TYPE
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The copied items, in row order
+    pub(crate) fn copy_selected(&self) -> Vec<String> {
+        self.copy_rows(&self.selected_rows())
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn set_add_mode(&self) {
+    // place the entries at the supplied rows on the clipboard
+    fn copy_rows(&self, rows: &[usize]) -> Vec<String> {
+        let entries = self.entries();
+        let selected = rows
+            .iter()
+            .copied()
+            .filter(|row| *row < entries.len())
+            .map(|row| (row, entries[row].clone()))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Vec::new();
+        }
+        let copied = selected
+            .iter()
+            .map(|(_, entry)| entry.clone())
+            .collect::<Vec<_>>();
         unsafe {
-            self.add_mode_action().activate(ActionEvent::Trigger);
+            let mut mime_data = QMimeData::new();
+            mime_data.set_text(&qs(clipboard::to_plain_text(&copied)));
+            mime_data.set_data(
+                &qs(JSON_MIME_TYPE),
+                &QByteArray::from_slice(clipboard::to_json(&copied).as_bytes()),
+            );
+            mime_data.set_data(
+                &qs(WITHS_MIME_TYPE),
+                &QByteArray::from_slice(clipboard::encode_withs(&selected).as_bytes()),
+            );
+            // the clipboard takes ownership of the mime data
+            QGuiApplication::clipboard().set_mime_data_1a(mime_data.into_ptr());
         }
+        copied.iter().map(ToString::to_string).collect()
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn set_find_mode(&self) {
-        unsafe {
-            self.find_mode_action().activate(ActionEvent::Trigger);
+    /// Place the selected entries on the clipboard, as per `copy_selected`,
+    /// and remove them from the list. Locked entries are neither copied nor
+    /// removed.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The removed items, in row order
+    pub(crate) fn cut_selected(&self) -> Vec<String> {
+        let rows = self.unlocked_selected_rows();
+        if self.copy_rows(&rows).is_empty() {
+            return Vec::new();
         }
+        self.remove_rows_or_log(&rows)
     }
 
-    fn _find_item<'a>(
-        item: QRef<QString>,
-        model: &MutPtr<QStandardItemModel>,
-    ) -> Option<MutPtr<QStandardItem>> {
-        unsafe {
-            let mut location = model.find_items_2a(item, MatchFlag::MatchCaseSensitive.into());
-            if location.count() == 0 {
-                return None;
-            }
-            let first = location.take_first();
-            Some(first)
-        }
+    /// Inform the user of the entries which were rejected by a paste
+    ///
+    /// # Arguments
+    /// * `report` - The PasteReport
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn report_paste(&self, report: &PasteReport) {
+        self.report_rejected("Paste", report);
     }
 
-    fn _scroll_to_item<'a>(
-        item: QRef<QString>,
-        view: &mut MutPtr<QListView>,
-        model: &mut MutPtr<QStandardItemModel>,
-        select: bool,
-    ) -> bool {
+    /// Retrieve the rows of the selected items, in ascending order
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<usize>
+    pub(crate) fn selected_rows(&self) -> Vec<usize> {
         unsafe {
-            if let Some(item) = Self::_find_item(item, model) {
-                let idx = item.index();
-                view.scroll_to_1a(&idx);
-                if select == true {
-                    Self::_select_item(idx.as_ref(), &view);
-                }
-                return true;
-            }
-            false
+            // the view's indexes refer to the proxy, so we map them
+            // back to the rows of the model
+            let selected = self.view.selection_model().selected_indexes();
+            let mut rows = (0..selected.size())
+                .map(|x| self.proxy.map_to_source(selected.at(x)).row() as usize)
+                .collect::<Vec<_>>();
+            rows.sort_unstable();
+            rows.dedup();
+            rows
         }
     }
 
-    unsafe fn _select_item(item: QRef<QModelIndex>, view: &MutPtr<QListView>) {
-        view.selection_model().clear();
-        view.selection_model()
-            .set_current_index(item, SelectionFlag::SelectCurrent.into());
-    }
-
-    // setup the main widget, performing configuration, adding a
-    // layout, and registering ti with its parent, inserting it into
+    /// Retrieve the selected items, in the order they appear in the list
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items
+    pub(crate) fn selected_items(&self) -> Vec<String> {
+        let data = self.data.borrow();
+        self.selected_rows()
+            .into_iter()
+            .filter_map(|row| data.get(row).map(T::to_string))
+            .collect()
+    }
+
+    /// Retrieve the generation of the items, which is incremented whenever
+    /// they change
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * u64
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    /// Retrieve a handle to the item with the supplied name, or the
+    /// supplied name and version
+    ///
+    /// # Arguments
+    /// * `item` - The item, with or without a version
+    ///
+    /// # Returns
+    /// * Some(ItemHandle), or None if the item is not in the list
+    pub(crate) fn handle(&self, item: &str) -> Option<ItemHandle> {
+        let data = self.data.borrow();
+        let row = data.position(item)?;
+        let entry = data.get(row)?;
+        Some(ItemHandle::new(entry.to_string(), row, self.generation()))
+    }
+
+    /// Retrieve the row of the item identified by a handle
+    ///
+    /// # Arguments
+    /// * `handle` - The ItemHandle
+    ///
+    /// # Returns
+    /// * Ok wrapping the row, or StaleHandle if the items have changed
+    ///   since the handle was made
+    pub(crate) fn resolve(&self, handle: &ItemHandle) -> Result<usize, StaleHandle> {
+        let current = self.generation();
+        if handle.generation() != current {
+            return Err(StaleHandle {
+                item: handle.item().to_string(),
+                generation: handle.generation(),
+                current,
+            });
+        }
+        Ok(handle.row())
+    }
+
+    /// Retrieve the rows of the selected items which are not locked, in
+    /// ascending order
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<usize>
+    pub(crate) fn unlocked_selected_rows(&self) -> Vec<usize> {
+        let rows = self.selected_rows();
+        let data = self.data.borrow();
+        let (unlocked, locked): (Vec<_>, Vec<_>) =
+            rows.into_iter().partition(|row| !data.is_locked(*row));
+        if !locked.is_empty() {
+            log::warn!(
+                "{} selected items are locked by {}",
+                locked.len(),
+                data.lock_source().unwrap_or_default()
+            );
+        }
+        unlocked
+    }
+
+    /// Remove the selected items from the list, other than those which are
+    /// locked
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The removed items, in row order
+    pub(crate) fn remove_selected(&self) -> Vec<String> {
+        self.remove_rows_or_log(&self.unlocked_selected_rows())
+    }
+
+    // remove the items at the supplied rows, logging any failure
+    fn remove_rows_or_log(&self, rows: &[usize]) -> Vec<String> {
+        match self.remove_rows(rows) {
+            Ok(removed) => removed,
+            Err(e) => {
+                log::error!("unable to remove selected items: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Move the items at the supplied rows, as a contiguous block in their
+    /// current order, so that the first of them ends up at row `to`. The
+    /// moved items are selected, and the scroll position is preserved.
+    ///
+    /// # Arguments
+    /// * `rows` - The current rows of the items
+    /// * `to` - The row the first item should occupy after the move
+    ///
+    /// # Returns
+    /// * Ok wrapping the range of rows occupied by the items after the move,
+    ///   or an ItemModelError
+    pub(crate) fn move_rows(
+        &self,
+        rows: &[usize],
+        to: usize,
+    ) -> Result<Range<usize>, ItemModelError> {
+        let before = self.all_items();
+        let moved = self.data.borrow_mut().move_rows(rows, to)?;
+        if self.all_items() == before {
+            return Ok(moved);
+        }
+        self.record("Move", &before);
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+        // only the moved rows are taken and reinserted, rather than the view
+        // being rebuilt, so that the rest of the view, and its scroll
+        // position, are undisturbed
+        self.sync_guard(|| unsafe {
+            let mut model = self.model();
+            // the rows are taken from the bottom up, so that the remaining
+            // rows are unaffected by each removal
+            let taken = rows
+                .iter()
+                .rev()
+                .map(|row| model.take_row(*row as i32))
+                .collect::<Vec<_>>();
+            for (row, items) in moved.clone().zip(taken.iter().rev()) {
+                model.insert_row_int_q_list_of_q_standard_item(row as i32, items);
+            }
+        });
+        self.select_rows(moved.clone());
+        self.notify(WithsChange::Reordered {
+            items: self.all_items(),
+        });
+        Ok(moved)
+    }
+
+    /// Move the selected items by the supplied number of rows, gathering
+    /// them into a contiguous block. Negative offsets move them up.
+    ///
+    /// # Arguments
+    /// * `offset` - The number of rows to move the items by
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn move_selected_by(&self, offset: isize) {
+        let rows = self.selected_rows();
+        let first = match rows.first() {
+            Some(first) => *first as isize,
+            None => return,
+        };
+        self.move_selected_to((first + offset).max(0) as usize);
+    }
+
+    /// Move the selected items, as a contiguous block, so that the first of
+    /// them ends up at the supplied row. Rows past the end move the items
+    /// to the bottom of the list.
+    ///
+    /// # Arguments
+    /// * `row` - The row the first item should occupy after the move
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn move_selected_to(&self, row: usize) {
+        let rows = self.selected_rows();
+        if rows.is_empty() {
+            return;
+        }
+        if let Err(e) = self.move_rows(&rows, row) {
+            log::error!("unable to move selected items: {}", e);
+        }
+    }
+
+    /// Select the items at the supplied rows, replacing the selection, and
+    /// scroll the first of them into view
+    ///
+    /// # Arguments
+    /// * `rows` - The rows of the items
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn select_rows(&self, rows: Range<usize>) {
+        unsafe {
+            let mut selection_model = self.view.selection_model();
+            selection_model.clear();
+            for row in rows.clone() {
+                let idx = self
+                    .proxy
+                    .map_from_source(&self.model.index_2a(row as i32, 0));
+                if !idx.is_valid() {
+                    continue;
+                }
+                if row == rows.start {
+                    selection_model.set_current_index(&idx, SelectionFlag::Select.into());
+                    let mut view = self.view;
+                    view.scroll_to_2a(&idx, ScrollHint::EnsureVisible);
+                } else {
+                    selection_model.select_q_model_index_q_flags_selection_flag(
+                        &idx,
+                        SelectionFlag::Select.into(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Switch to add mode, presenting the item's key, and any versions of
+    /// it, among the combobox's completions
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn reveal_in_candidates(&self, item: &str) {
+        let name =
+            T::parse_item(item).map_or_else(|_| item.to_string(), |entry| entry.key().to_string());
+        self.set_add_mode();
+        unsafe {
+            let mut cbox = self.add_combobox();
+            cbox.set_edit_text(&qs(&name));
+            cbox.set_focus_0a();
+            let mut completer = self.completer;
+            completer.complete_0a();
+        }
+    }
+
+    /// Append an action to the context menu of the list. Once triggered, the
+    /// action's callback is invoked with the selected items.
+    ///
+    /// # Arguments
+    /// * `label` - The text of the action
+    /// * `callback` - The ContextActionCallback
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn add_context_action(&self, label: &str, callback: ContextActionCallback) {
+        self.context_actions
+            .borrow_mut()
+            .push((label.to_string(), callback));
+    }
+
+    /// Remove the actions appended to the context menu
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_context_actions(&self) {
+        self.context_actions.borrow_mut().clear();
+    }
+
+    /// Present the context menu of the list, acting upon the selected items
+    ///
+    /// # Arguments
+    /// * `pos` - The position of the request, in the view's viewport
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn show_context_menu(&self, pos: QRef<QPoint>) {
+        let rows = self.selected_rows();
+        let len = self.data.borrow().len();
+        let locked = {
+            let data = self.data.borrow();
+            rows.iter().filter(|row| data.is_locked(**row)).count()
+        };
+        // each move is offered only if it would change the order, matching
+        // the rows move_selected_by and move_selected_to move the items to
+        let (to_top, up, down, to_bottom) = {
+            let data = self.data.borrow();
+            let first = rows.first().copied().unwrap_or_default();
+            (
+                data.would_move_rows(&rows, 0),
+                data.would_move_rows(&rows, first.saturating_sub(1)),
+                data.would_move_rows(&rows, first + 1),
+                data.would_move_rows(&rows, len),
+            )
+        };
+        let selected = {
+            let data = self.data.borrow();
+            rows.iter()
+                .filter_map(|row| data.get(*row))
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        unsafe {
+            let mut menu = QMenu::new();
+            let has_selection = !selected.is_empty();
+            // locked items may be copied, but not otherwise edited
+            let has_unlocked = has_selection && locked < rows.len();
+            let mut remove = menu.add_action_q_string(&qs("Remove"));
+            remove.set_enabled(has_unlocked);
+            let mut copy = menu.add_action_q_string(&qs("Copy"));
+            copy.set_enabled(has_selection);
+            let toggle_label = if has_selection && !self.any_enabled(&rows) {
+                "Enable"
+            } else {
+                "Disable"
+            };
+            let mut toggle = menu.add_action_q_string(&qs(toggle_label));
+            toggle.set_enabled(has_unlocked);
+            menu.add_separator();
+            let mut move_top = menu.add_action_q_string(&qs("Move to Top"));
+            move_top.set_enabled(to_top);
+            let mut move_up = menu.add_action_q_string(&qs("Move Up"));
+            move_up.set_enabled(up);
+            let mut move_down = menu.add_action_q_string(&qs("Move Down"));
+            move_down.set_enabled(down);
+            let mut move_bottom = menu.add_action_q_string(&qs("Move to Bottom"));
+            move_bottom.set_enabled(to_bottom);
+            menu.add_separator();
+            let mut reveal = menu.add_action_q_string(&qs("Reveal in Candidates"));
+            reveal.set_enabled(selected.len() == 1);
+            let custom = {
+                let actions = self.context_actions.borrow();
+                if !actions.is_empty() {
+                    menu.add_separator();
+                }
+                actions
+                    .iter()
+                    .map(|(label, _)| menu.add_action_q_string(&qs(label)))
+                    .collect::<Vec<_>>()
+            };
+
+            let global = self.view.viewport().map_to_global(pos);
+            let chosen = menu.exec_1a_mut(&global);
+            if chosen.is_null() {
+                return;
+            }
+            let chosen = chosen.as_raw_ptr();
+            if chosen == remove.as_raw_ptr() {
+                self.remove_selected();
+            } else if chosen == copy.as_raw_ptr() {
+                self.copy_selected();
+            } else if chosen == toggle.as_raw_ptr() {
+                self.toggle_selected_enabled();
+            } else if chosen == move_top.as_raw_ptr() {
+                self.move_selected_to(0);
+            } else if chosen == move_up.as_raw_ptr() {
+                self.move_selected_by(-1);
+            } else if chosen == move_down.as_raw_ptr() {
+                self.move_selected_by(1);
+            } else if chosen == move_bottom.as_raw_ptr() {
+                self.move_selected_to(len);
+            } else if chosen == reveal.as_raw_ptr() {
+                self.reveal_in_candidates(&selected[0]);
+            } else if let Some(index) = custom
+                .iter()
+                .position(|action| action.as_raw_ptr() == chosen)
+            {
+                // the callback may add further actions, so we may not hold
+                // a borrow while invoking it
+                let actions = self.context_actions.replace(Vec::new());
+                if let Some((_, callback)) = actions.get(index) {
+                    callback(&selected);
+                }
+                let mut added = self.context_actions.replace(actions);
+                self.context_actions.borrow_mut().append(&mut added);
+            }
+        }
+    }
+
+    /// Enable or disable the items at the supplied rows, as a single edit.
+    /// The list is left untouched if any of the rows is out of range.
+    ///
+    /// # Arguments
+    /// * `rows` - The rows of the items
+    /// * `enabled` - Whether the items should be enabled
+    ///
+    /// # Returns
+    /// * Ok wrapping the rows which changed, or an ItemModelError
+    pub(crate) fn set_rows_enabled(
+        &self,
+        rows: &[usize],
+        enabled: bool,
+    ) -> Result<Vec<usize>, ItemModelError> {
+        let before = self.all_items();
+        let mut data = self.data.borrow().clone();
+        let mut changed = Vec::new();
+        for row in rows {
+            if data.set_enabled(*row, enabled)? {
+                changed.push(*row);
+            }
+        }
+        changed.sort_unstable();
+        changed.dedup();
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+        self.data.replace(data);
+        let label = if enabled { "Enable" } else { "Disable" };
+        self.record(label, &before);
+        self.sync_guard(|| {
+            for row in &changed {
+                self.update_item(*row);
+            }
+        });
+        let changes = {
+            let data = self.data.borrow();
+            changed
+                .iter()
+                .filter_map(|row| data.get(*row).map(|entry| (*row, entry)))
+                .map(|(row, entry)| WithsChange::Toggled {
+                    item: entry.to_string(),
+                    row,
+                    enabled,
+                })
+                .collect()
+        };
+        self.notify_all(changes);
+        Ok(changed)
+    }
+
+    /// Enable or disable an item
+    ///
+    /// # Arguments
+    /// * `item` - The item, with or without a version
+    /// * `enabled` - Whether the item should be enabled
+    ///
+    /// # Returns
+    /// * Ok, or NotFound if the item is not in the list
+    pub(crate) fn set_item_enabled(&self, item: &str, enabled: bool) -> Result<(), ItemModelError> {
+        let row = self
+            .data
+            .borrow()
+            .position(item)
+            .ok_or_else(|| ItemModelError::NotFound(item.to_string()))?;
+        self.set_rows_enabled(&[row], enabled)?;
+        Ok(())
+    }
+
+    /// Determine whether an item is enabled
+    ///
+    /// # Arguments
+    /// * `item` - The item, with or without a version
+    ///
+    /// # Returns
+    /// * Some(bool), or None if the item is not in the list
+    pub(crate) fn is_item_enabled(&self, item: &str) -> Option<bool> {
+        let data = self.data.borrow();
+        data.position(item)
+            .and_then(|row| data.get(row))
+            .map(|entry| entry.is_enabled())
+    }
+
+    /// Disable the selected items, or enable them if all of them are
+    /// disabled. Locked items are left enabled.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn toggle_selected_enabled(&self) {
+        let rows = self.unlocked_selected_rows();
+        let enabled = !self.any_enabled(&rows);
+        if let Err(e) = self.set_rows_enabled(&rows, enabled) {
+            log::error!("unable to toggle the selected items: {}", e);
+        }
+    }
+
+    /// Update the ItemModel after the user has checked or unchecked an
+    /// item of the view. Other changes to the view's items are ignored.
+    ///
+    /// # Arguments
+    /// * `item` - The item of the qt model which changed
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn sync_enabled_from_view(&self, item: MutPtr<QStandardItem>) {
+        if self.syncing.get() || item.is_null() {
+            return;
+        }
+        let (row, key, checked) = unsafe {
+            (
+                item.row(),
+                item.data_1a(Self::key_role()).to_string().to_std_string(),
+                item.check_state() == CheckState::Checked,
+            )
+        };
+        if row < 0 {
+            return;
+        }
+        let row = row as usize;
+        // rows being dropped do not yet correspond to the ItemModel, and
+        // are reconciled once the drop completes
+        let toggled = self
+            .data
+            .borrow()
+            .get(row)
+            .is_some_and(|entry| entry.to_string() == key && entry.is_enabled() != checked);
+        if toggled {
+            if let Err(e) = self.set_rows_enabled(&[row], checked) {
+                log::error!("unable to sync the enabled state from the view: {}", e);
+            }
+        }
+    }
+
+    /// Determine whether the model would reject the item as a duplicate,
+    /// taking the allowed duplicates into account
+    ///
+    /// # Arguments
+    /// * `item` - The name of the item
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_duplicate(&self, item: &str) -> bool {
+        self.data.borrow().is_duplicate(item)
+    }
+
+    /// Retrieve a vector of Strings for items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub(crate) fn items(&self) -> Vec<String> {
+        self.data.borrow().items()
+    }
+
+    /// Retrieve all of the items, enabled or not. Disabled items carry the
+    /// DISABLED_PREFIX
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub(crate) fn all_items(&self) -> Vec<String> {
+        self.data.borrow().all_items()
+    }
+
+    /// Retrieve the entries of the list
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec of the items
+    pub(crate) fn entries(&self) -> Vec<T> {
+        self.data.borrow().entries().to_vec()
+    }
+
+    /// Update the ItemModel to reflect the rows of the qt model. This is
+    /// invoked after the view has changed the qt model on its own, as it
+    /// does when the user drags items around, or between lists. Dropped
+    /// items are validated as per a paste, and those rejected are removed
+    /// from the view.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn sync_from_view(&self) {
+        if self.syncing.get() {
+            return;
+        }
+        let keys = self.view_keys();
+        let before = self.all_items();
+        if keys == before {
+            return;
+        }
+        let data = self.data.borrow().clone();
+        let reconciled = dnd::reconcile(&data, &keys, |name| self.is_candidate(name));
+        // locked items are returned to the top of the list, wherever they
+        // were dropped
+        let mut pinned = data;
+        if let Err(e) = pinned.set_items(reconciled.items) {
+            log::error!("unable to sync withs from view: {}", e);
+            self.rebuild_view();
+            self.refresh_decorations();
+            return;
+        }
+        let items = pinned.all_items();
+        let changes = change::changes(&before, &items);
+        if !changes.is_empty() {
+            self.data.replace(pinned);
+            let label = match changes.as_slice() {
+                [WithsChange::Reordered { .. }] => "Move",
+                _ => "Drop",
+            };
+            self.record(label, &before);
+        }
+        if items != keys {
+            self.rebuild_view();
+            if changes.is_empty() {
+                self.refresh_decorations();
+            }
+        }
+        let added = changes
+            .iter()
+            .filter_map(|change| match change {
+                WithsChange::Added { item, .. } => Some(item.clone()),
+                _ => None,
+            })
+            .collect();
+        if !changes.is_empty() {
+            self.notify_all(changes);
+        }
+        self.report_rejected(
+            "Drop",
+            &PasteReport {
+                added,
+                rejected: reconciled.rejected,
+            },
+        );
+    }
+
+    /// Sync the ItemModel from the qt model once control returns to the
+    /// event loop. The view moves rows by inserting copies and then
+    /// removing the originals, and a drag between lists inserts and removes
+    /// rows in separate models, so we wait for the drag to complete.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn schedule_sync_from_view(&self) {
+        if self.syncing.get() {
+            return;
+        }
+        unsafe {
+            let mut sync_timer = self.sync_timer;
+            sync_timer.start_0a();
+        }
+    }
+
+    /// Retrieve the timer which syncs the ItemModel from the qt model
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr to the QTimer
+    pub(crate) fn sync_timer(&self) -> MutPtr<QTimer> {
+        self.sync_timer
+    }
+
+    /// Determine whether the items differ from the baseline
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the list has unsaved edits
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.data.borrow().is_dirty()
+    }
+
+    /// Register the handler invoked when the user saves the list,
+    /// replacing any previously registered handler
+    ///
+    /// # Arguments
+    /// * `callback` - The save handler
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_save_callback(&self, callback: SaveCallback) {
+        self.save_callback.replace(Some(Rc::from(callback)));
+    }
+
+    /// Invoke the save handler with all of the current items, disabled
+    /// items carrying the DISABLED_PREFIX. If the handler reports success,
+    /// the items become the new baseline.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the items were saved
+    pub(crate) fn save(&self) -> bool {
+        if self.is_save_blocked() {
+            log::warn!("unable to save while the items have errors");
+            return false;
+        }
+        // the handler may replace itself, so we may not hold a borrow
+        // while invoking it
+        let callback = self.save_callback.borrow().clone();
+        let saved = match callback {
+            Some(callback) => callback(self.all_items()),
+            None => {
+                log::warn!("no save handler registered");
+                false
+            }
+        };
+        if saved {
+            self.data.borrow_mut().mark_clean();
+            self.update_buttons();
+        }
+        saved
+    }
+
+    /// Add a validator which reports diagnostics against the items
+    ///
+    /// # Arguments
+    /// * `validator` - The Validator
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn add_validator(&self, validator: Box<dyn Validator<T>>) {
+        self.validators.borrow_mut().push(validator);
+        self.refresh_decorations();
+        self.update_buttons();
+    }
+
+    /// Remove all of the validators
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_validators(&self) {
+        self.validators.borrow_mut().clear();
+        self.refresh_decorations();
+        self.update_buttons();
+    }
+
+    /// Retrieve the diagnostics reported by the validators
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of ItemDiagnostics, ordered by row
+    pub(crate) fn diagnostics(&self) -> Vec<ItemDiagnostic> {
+        self.diagnostics.borrow().clone()
+    }
+
+    /// Determine whether any of the diagnostics is an error
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn has_errors(&self) -> bool {
+        has_errors(&self.diagnostics.borrow())
+    }
+
+    /// Set whether saving is prevented while there are errors
+    ///
+    /// # Arguments
+    /// * `block` - Whether to block saving
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_block_save_on_errors(&self, block: bool) {
+        self.block_save_on_errors.set(block);
+        self.update_buttons();
+    }
+
+    // saving is blocked by errors, when configured to be
+    fn is_save_blocked(&self) -> bool {
+        self.block_save_on_errors.get() && self.has_errors()
+    }
+
+    /// Restore the items from the baseline, discarding any edits
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn revert(&self) {
+        if !self.is_dirty() {
+            return;
+        }
+        let before = self.all_items();
+        self.data.borrow_mut().revert();
+        self.record("Revert", &before);
+        self.rebuild_view();
+        self.notify(WithsChange::Reset);
+    }
+
+    /// Undo the last edit
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether there was an edit to undo
+    pub(crate) fn undo(&self) -> bool {
+        let items = self.all_items();
+        let restored = self
+            .history
+            .borrow_mut()
+            .undo()
+            .map(|edit| edit.undo(&items));
+        self.restore(restored)
+    }
+
+    /// Redo the last undone edit
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether there was an edit to redo
+    pub(crate) fn redo(&self) -> bool {
+        let items = self.all_items();
+        let restored = self
+            .history
+            .borrow_mut()
+            .redo()
+            .map(|edit| edit.redo(&items));
+        self.restore(restored)
+    }
+
+    /// Retrieve the undo history
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ref wrapping the History
+    pub(crate) fn history(&self) -> Ref<History<Edit>> {
+        self.history.borrow()
+    }
+
+    /// Retrieve the registered change callbacks, in order to register
+    /// additional callbacks
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * RefMut wrapping the ChangeCallbacks
+    pub(crate) fn callbacks_mut(&self) -> RefMut<ChangeCallbacks> {
+        self.callbacks.borrow_mut()
+    }
+
+    /// add an item to the pulldown
+    ///
+    /// # Arguments
+    /// * The item to be added, as a &str or String
+    #[allow(dead_code)]
+    pub(crate) fn add_item<I>(&self, item: I) -> Result<usize, ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        self.add_item_to(item.as_ref())
+    }
+
+    /// add an item to the pulldown
+    ///
+    /// # Arguments
+    /// * The item to be found, as a &MutPtr<QString>
+    #[allow(dead_code)]
+    pub(crate) fn find_item(&self, item: QRef<QString>) -> Option<MutPtr<QStandardItem>> {
+        Self::_find_item(item, &self.data.borrow(), &self.model())
+    }
+
+    /// scroll to the provided item in the list, looked up via
+    /// `ItemModel::position`
+    ///
+    /// # Arguments
+    /// * `item` - A Ref wrapped QString.
+    /// * `select_item` - a boolean indicating whether the item should be selected as well as
+    ///   scrolled to
+    #[allow(dead_code)]
+    pub(crate) fn scroll_to_item(&self, item: QRef<QString>, select_item: bool) -> bool {
+        Self::_scroll_to_item(
+            item,
+            &self.data.borrow(),
+            &mut self.view(),
+            &mut self.model(),
+            &self.proxy,
+            select_item,
+        )
+    }
+
+    /// Select the provided item given a Ref wrapped QModelIndex
+    ///
+    /// # Arguments
+    /// * `item` - QModelIndex of the item we wish to select
+    ///
+    /// # Returns
+    /// * None
+    #[allow(dead_code)]
+    pub(crate) fn select_item(&self, item: QRef<QModelIndex>) {
+        unsafe {
+            let idx = self.proxy.map_from_source(item);
+            if !idx.is_valid() {
+                return;
+            }
+            Self::_select_item(idx.as_ref(), &self.view);
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Set comboboc items, replacing any extant items
+    ///
+    /// # Arguments
+    /// * `items` - Vector of items
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_cb_items<'c, I>(&self, items: Vec<I>)
+    where
+        I: AsRef<str>,
+    {
+        unsafe {
+            self.remove_cb_items();
+            self.add_combobox().add_item_q_string(&qs(""));
+            for item in items {
+                self.add_combobox().add_item_q_string(&qs(item.as_ref()));
+            }
+            self.update_completions(&self.add_combobox().current_text().to_std_string());
+        }
+    }
+
+    /// Replace the combobox items with the candidates of the provider, which
+    /// are loaded on a worker thread and added to the combobox in batches.
+    /// A loading indicator is displayed until all of the candidates have
+    /// arrived. Any load already in progress is cancelled.
+    ///
+    /// # Arguments
+    /// * `provider` - The CandidateProvider to load from
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn load_cb_items(&self, provider: Arc<dyn CandidateProvider>) {
+        self.remove_cb_items();
+        unsafe {
+            self.add_combobox().add_item_q_string(&qs(""));
+        }
+        self.load
+            .replace(Some(CandidateLoad::start(provider, LOAD_BATCH_SIZE)));
+        self.update_loading();
+    }
+
+    /// Cancel the load of combobox items in progress, if any. The items
+    /// which have already arrived are retained.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn cancel_cb_items_load(&self) {
+        if self.load.replace(None).is_some() {
+            self.update_loading();
+        }
+    }
+
+    /// Determine whether combobox items are loading in the background
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_loading_cb_items(&self) -> bool {
+        self.load.borrow().is_some()
+    }
+
+    /// Add the combobox items, and the candidates of the candidate provider,
+    /// which have arrived from the worker threads since the last poll. This
+    /// is invoked by the load timer.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn poll_cb_items_load(&self) {
+        let items = Self::poll_load(&self.load);
+        let provided = Self::poll_load(&self.provider_load);
+        if items.is_none() && provided.is_none() {
+            return;
+        }
+        if let Some(items) = items {
+            unsafe {
+                let mut cbox = self.add_combobox();
+                for item in items {
+                    cbox.add_item_q_string(&qs(item));
+                }
+            }
+        }
+        if let (Some(candidates), Some(loaded)) = (provided, self.provided.borrow_mut().as_mut()) {
+            loaded.extend(candidates);
+        }
+        self.update_loading();
+        let text = unsafe { self.add_combobox().current_text().to_std_string() };
+        self.update_completions(&text);
+    }
+
+    /// Retrieve the timer which polls the load of combobox items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr<QTimer>
+    pub(crate) fn load_timer(&self) -> MutPtr<QTimer> {
+        self.load_timer
+    }
+
+    /// Retrieve the combobox items, skipping the empty item
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub(crate) fn cb_items(&self) -> Vec<String> {
+        unsafe {
+            let cbox = self.add_combobox();
+            (0..cbox.count())
+                .map(|cnt| cbox.item_text(cnt).to_std_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        }
+    }
+
+    /// Install a provider which supplies the candidates offered for
+    /// completion, in place of the combobox items. The provider's candidates
+    /// are loaded on a worker thread, and completions and validation draw on
+    /// the candidates which have arrived, so that the provider is never
+    /// queried on the gui thread. Any provider load in progress is cancelled.
+    ///
+    /// # Arguments
+    /// * `provider` - The CandidateProvider
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_candidate_provider(&self, provider: Arc<dyn CandidateProvider>) {
+        self.provided.replace(Some(Vec::new()));
+        self.provider_load
+            .replace(Some(CandidateLoad::start(provider, LOAD_BATCH_SIZE)));
+        self.update_loading();
+        let text = unsafe { self.add_combobox().current_text().to_std_string() };
+        self.update_completions(&text);
+    }
+
+    /// Remove the candidate provider, cancelling the load of its candidates,
+    /// and reverting to the combobox items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_candidate_provider(&self) {
+        self.provider_load.replace(None);
+        self.provided.replace(None);
+        self.update_loading();
+        let text = unsafe { self.add_combobox().current_text().to_std_string() };
+        self.update_completions(&text);
+    }
+
+    /// Retrieve the candidates which may complete the text entered in
+    /// the combobox: the candidates loaded from the candidate provider, if
+    /// one is installed, or the combobox items otherwise. All of them are
+    /// returned, to be fuzzy matched against the text.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub(crate) fn candidates(&self) -> Vec<String> {
+        match self.provided.borrow().as_ref() {
+            Some(provided) => provided.clone(),
+            None => self.cb_items(),
+        }
+    }
+
+    /// Determine whether the supplied key is a candidate, either
+    /// of the candidate provider, or of the combobox items. A provider's
+    /// candidates which have yet to arrive from the worker thread are not
+    /// yet candidates.
+    ///
+    /// # Arguments
+    /// * `name` - The key of an item
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_candidate(&self, name: &str) -> bool {
+        match self.provided.borrow().as_ref() {
+            Some(provided) => provided.iter().any(|candidate| candidate == name),
+            None => self.cb_items().iter().any(|item| item == name),
+        }
+    }
+
+    /// Set the versions available for a package. Once the user has entered
+    /// the package name followed by a dash or comparison operator, the versions
+    /// are offered as completions.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `versions` - Vector of versions
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_package_versions<I>(&self, package: &str, versions: Vec<I>)
+    where
+        I: AsRef<str>,
+    {
+        self.versions.borrow_mut().insert(
+            package.to_string(),
+            versions.iter().map(|v| v.as_ref().to_string()).collect(),
+        );
+    }
+
+    /// Remove all of the package versions
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn clear_package_versions(&self) {
+        self.versions.borrow_mut().clear();
+    }
+
+    /// Update the combobox completions given the text entered by the user.
+    /// In add mode, completes versions once a package with known versions has
+    /// been chosen, and package names otherwise. In the find and filter modes,
+    /// completes the items in the list. The completions are fuzzy matched
+    /// against the text and ranked best first, with the exception of versions,
+    /// which retain the order in which they were supplied.
+    ///
+    /// # Arguments
+    /// * `text` - The text entered in the combobox
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn update_completions(&self, text: &str) {
+        let versions = if self.is_add_active() {
+            version_completions(text, &self.versions.borrow())
+        } else {
+            None
+        };
+        let completions = match versions {
+            Some(versions) => versions
+                .iter()
+                .filter_map(|version| fuzzy_match(text, version))
+                .collect(),
+            None if self.is_add_active() => fuzzy_rank(text, self.candidates()),
+            None => fuzzy_rank(text, self.items()),
+        };
+        if *self.completions.borrow() == completions {
+            return;
+        }
+        unsafe {
+            let mut completer_model = self.completer_model;
+            completer_model.clear();
+            for (row, completion) in completions.iter().enumerate() {
+                let mut si = QStandardItem::new();
+                // highlighted completions are displayed by a label instead
+                if row >= MAX_HIGHLIGHTED_COMPLETIONS || completion.positions.is_empty() {
+                    si.set_text(&qs(&completion.candidate));
+                }
+                si.set_data_2a(
+                    &QVariant::from_q_string(&qs(&completion.candidate)),
+                    Self::key_role(),
+                );
+                completer_model.append_row_q_standard_item(si.into_ptr());
+            }
+        }
+        self.completions.replace(completions);
+        self.highlight_completions();
+    }
+
+    /// Retrieve the best of the current completions
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(completion), or None if there are no completions
+    pub(crate) fn best_completion(&self) -> Option<String> {
+        self.completions
+            .borrow()
+            .first()
+            .map(|completion| completion.candidate.clone())
+    }
+
+    /// Display the characters of the completions which match the user's
+    /// text in bold. The completer rebuilds its popup as the user types,
+    /// discarding the highlights, so this must be invoked whenever the popup's
+    /// model is reset.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn highlight_completions(&self) {
+        let completions = self.completions.borrow();
+        unsafe {
+            let mut popup = self.completer.popup();
+            let model = popup.model();
+            let rows = (model.row_count_0a() as usize).min(MAX_HIGHLIGHTED_COMPLETIONS);
+            for row in 0..rows {
+                let idx = model.index_2a(row as i32, 0);
+                let key = idx.data_1a(Self::key_role()).to_string().to_std_string();
+                let completion = match completions.iter().find(|c| c.candidate == key) {
+                    Some(completion) if !completion.positions.is_empty() => completion,
+                    _ => continue,
+                };
+                let mut label = QLabel::from_q_string(&qs(completion.highlighted()));
+                label.set_text_format(TextFormat::RichText);
+                label.set_attribute_1a(WidgetAttribute::WATransparentForMouseEvents);
+                label.set_object_name(&qs("WithsCompletionLabel"));
+                popup.set_index_widget(&idx, label.into_ptr());
+            }
+        }
+    }
+
+    /// Retrieve the model backing the completer's popup, which is reset
+    /// as the user types
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr<QAbstractItemModel>
+    pub(crate) fn completion_model(&self) -> MutPtr<QAbstractItemModel> {
+        unsafe { self.completer.completion_model() }
+    }
+
+    #[allow(dead_code)]
+    /// Remove all items from the combobox, cancelling any load of items
+    /// in progress
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns None
+    pub(crate) fn remove_cb_items(&self) {
+        self.cancel_cb_items_load();
+        unsafe {
+            self.add_combobox().clear();
+        }
+        self.update_completions("");
+    }
+
+    /// Set the keys which may appear more than once, as long as each
+    /// appearance has different text
+    ///
+    /// # Arguments
+    /// * `keys` - Vector of keys
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_allowed_duplicates(&self, keys: Vec<String>) {
+        self.data.borrow_mut().set_allowed_duplicates(keys);
+    }
+
+    /// Lock the supplied items at the top of the list, in the supplied
+    /// order, replacing any previously locked. Absent items are added.
+    /// Locked items may not be removed, moved or disabled, by the user or
+    /// programmatically. The change is not recorded in the undo history, as
+    /// the items are mandated rather than edited.
+    ///
+    /// # Arguments
+    /// * `items` - The items to lock
+    /// * `source` - Describes what locked the items, for their tooltips
+    ///
+    /// # Returns
+    /// * Ok, or the first ItemModelError encountered
+    pub(crate) fn set_locked_items<I>(
+        &self,
+        items: Vec<I>,
+        source: &str,
+    ) -> Result<(), ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        let before = self.all_items();
+        self.data.borrow_mut().set_locked_items(items, source)?;
+        self.rebuild_view();
+        let changes = change::changes(&before, &self.all_items());
+        // the lock and its source are displayed even if no items moved
+        if changes.is_empty() {
+            self.refresh_decorations();
+        } else {
+            self.notify_all(changes);
+        }
+        Ok(())
+    }
+
+    /// Retrieve the locked items, in order
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub(crate) fn locked_items(&self) -> Vec<String> {
+        self.data
+            .borrow()
+            .locked_items()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Determine whether an item is locked
+    ///
+    /// # Arguments
+    /// * `item` - The item, with or without a version
+    ///
+    /// # Returns
+    /// * Some(bool), or None if the item is not in the list
+    pub(crate) fn is_item_locked(&self, item: &str) -> Option<bool> {
+        let data = self.data.borrow();
+        data.position(item).map(|row| data.is_locked(row))
+    }
+
+    /// Change the max number of items displayed in the combobox's dropdown
+    /// list
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_cb_max_visible_items(&self, max: i32) {
+        unsafe {
+            self.add_combobox().set_max_visible_items(max);
+        }
+    }
+
+    /// Given a path as a &str to a stylesheet, apply it to the components.
+    ///
+    /// # Arguments
+    /// * `sheet` - Path to the qss stylesheet
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn set_stylesheet(&self, sheet: &str) {
+        load_stylesheet(sheet, self.main);
+    }
+
+    pub(crate) fn find_mode_action(&self) -> MutPtr<QAction> {
+        self.mode_toolbar.find_mode_action
+    }
+
+    pub(crate) fn add_mode_action(&self) -> MutPtr<QAction> {
+        self.mode_toolbar.add_mode_action
+    }
+
+    pub(crate) fn filter_mode_action(&self) -> MutPtr<QAction> {
+        self.mode_toolbar.filter_mode_action
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn set_add_mode(&self) {
+        unsafe {
+            self.add_mode_action().activate(ActionEvent::Trigger);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn set_find_mode(&self) {
+        unsafe {
+            self.find_mode_action().activate(ActionEvent::Trigger);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn set_filter_mode(&self) {
+        unsafe {
+            self.filter_mode_action().activate(ActionEvent::Trigger);
+        }
+    }
+
+    // items are looked up by key in the ItemModel, as the text displayed
+    // by the view may differ from the item's text (eg a version badge)
+    fn _find_item(
+        item: QRef<QString>,
+        data: &ItemModel<T>,
+        model: &MutPtr<QStandardItemModel>,
+    ) -> Option<MutPtr<QStandardItem>> {
+        unsafe {
+            let row = data.position(item.to_std_string().as_str())?;
+            let found = model.item_1a(row as i32);
+            if found.is_null() {
+                return None;
+            }
+            Some(found)
+        }
+    }
+
+    fn _scroll_to_item(
+        item: QRef<QString>,
+        data: &ItemModel<T>,
+        view: &mut MutPtr<QListView>,
+        model: &mut MutPtr<QStandardItemModel>,
+        proxy: &MutPtr<QSortFilterProxyModel>,
+        select: bool,
+    ) -> bool {
+        unsafe {
+            if let Some(item) = Self::_find_item(item, data, model) {
+                // items hidden by the filter may not be scrolled to
+                let idx = proxy.map_from_source(&item.index());
+                if !idx.is_valid() {
+                    return false;
+                }
+                view.scroll_to_1a(&idx);
+                if select {
+                    Self::_select_item(idx.as_ref(), view);
+                }
+                return true;
+            }
+            false
+        }
+    }
+
+    unsafe fn _select_item(item: QRef<QModelIndex>, view: &MutPtr<QListView>) {
+        view.selection_model().clear();
+        view.selection_model()
+            .set_current_index(item, SelectionFlag::SelectCurrent.into());
+    }
+
+    // Hand the filter text to the proxy model, translated into a regular
+    // expression according to the filter match strategy
+    fn apply_filter(&self) {
+        let text = self.filter_text.borrow();
+        let regex = if text.is_empty() {
+            String::new()
+        } else {
+            self.filter_match.get().to_regex(&text)
+        };
+        unsafe {
+            let regexp = QRegExp::new_3a(
+                &qs(&regex),
+                CaseSensitivity::CaseInsensitive,
+                PatternSyntax::RegExp2,
+            );
+            if !regexp.is_valid() {
+                log::debug!("invalid filter expression: {}", regex);
+                return;
+            }
+            let mut proxy = self.proxy;
+            proxy.set_filter_reg_exp_q_reg_exp(&regexp);
+        }
+    }
+
+    // Record an edit in the undo history, once it has been made, as the
+    // changes between the items prior to the edit and the current items
+    fn record<L>(&self, label: L, before: &[String])
+    where
+        L: Into<String>,
+    {
+        let edit = Edit::between(before, &self.all_items());
+        if !edit.is_empty() {
+            self.history.borrow_mut().record(label, edit);
+        }
+    }
+
+    // Restore the items from an edit undone or redone in the undo history
+    fn restore(&self, items: Option<Vec<String>>) -> bool {
+        let items = match items {
+            Some(items) => items,
+            None => return false,
+        };
+        if let Err(e) = self.data.borrow_mut().set_items(items) {
+            log::error!("unable to restore items from history: {}", e);
+            return false;
+        }
+        self.rebuild_view();
+        self.notify(WithsChange::Reset);
+        true
+    }
+
+    // Inform the registered callbacks of a change to the list
+    fn notify(&self, change: WithsChange) {
+        self.notify_all(vec![change]);
+    }
+
+    // Inform the registered callbacks of a series of changes to the list
+    fn notify_all(&self, changes: Vec<WithsChange>) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+        self.refresh_decorations();
+        self.update_buttons();
+        // the find and filter modes complete the items in the list
+        if !self.is_add_active() {
+            let text = unsafe { self.add_combobox().current_text().to_std_string() };
+            self.update_completions(&text);
+        }
+        let items = self.all_items();
+        // the callbacks may edit the list, or register further callbacks,
+        // so we may not hold a borrow while invoking them
+        let callbacks = self.callbacks.borrow().clone();
+        callbacks.dispatch(&changes, &items);
+    }
+
+    // Inform the user of the entries which were rejected by a paste or drop
+    fn report_rejected(&self, title: &str, report: &PasteReport) {
+        if report.is_complete() {
+            return;
+        }
+        let rejected = report
+            .rejected
+            .iter()
+            .map(|(entry, reason)| format!("{} - {}", entry, reason))
+            .collect::<Vec<_>>();
+        log::info!(
+            "rejected {} entries: {}",
+            title.to_lowercase(),
+            rejected.join(", ")
+        );
+        unsafe {
+            QMessageBox::warning_q_widget2_q_string(
+                self.main,
+                &qs(title),
+                &qs(format!(
+                    "Added {} of {} entries. Rejected:\n\n{}",
+                    report.added.len(),
+                    report.added.len() + report.rejected.len(),
+                    rejected.join("\n")
+                )),
+            );
+        }
+    }
+
+    // Show the loading indicator, and poll, while either the combobox items
+    // or the provider's candidates are loading
+    fn update_loading(&self) {
+        let loading = self.load.borrow().is_some() || self.provider_load.borrow().is_some();
+        unsafe {
+            let mut loading_label = self.loading_label;
+            loading_label.set_visible(loading);
+            let mut load_timer = self.load_timer;
+            if loading {
+                load_timer.start_0a();
+            } else {
+                load_timer.stop();
+            }
+        }
+    }
+
+    // Collect the candidates which have arrived from the load since the
+    // last poll, ending the load once it has finished or failed. Returns
+    // None if nothing has arrived
+    fn poll_load(load: &RefCell<Option<CandidateLoad>>) -> Option<Vec<String>> {
+        let events = load.borrow().as_ref()?.poll(MAX_LOAD_BATCHES_PER_POLL);
+        if events.is_empty() {
+            return None;
+        }
+        let mut candidates = Vec::new();
+        for event in events {
+            match event {
+                LoadEvent::Batch(batch) => candidates.extend(batch),
+                LoadEvent::Finished => {
+                    load.replace(None);
+                }
+                LoadEvent::Failed(e) => {
+                    log::error!("unable to load candidates: {}", e);
+                    load.replace(None);
+                }
+            }
+        }
+        Some(candidates)
+    }
+
+    // Map each item implied by others to the items implying it
+    fn implied_by(&self) -> HashMap<String, Vec<String>> {
+        match self.resolver.borrow().as_ref() {
+            Some(resolver) => dependency::implied_items(resolver.as_ref(), &self.items()),
+            None => HashMap::new(),
+        }
+    }
+
+    // Update the presentation of each item to reflect its relationship to
+    // the others, and the diagnostics reported against it. Items implied by
+    // others are displayed in italics, and items with diagnostics display the
+    // icon of the most severe. The tooltips carry the details. The view's
+    // `diagnostics` property holds the most severe diagnostic of all, for
+    // the benefit of stylesheets (eg `QListView[diagnostics="error"]`).
+    // Each item records the decorations it was last given, and only those
+    // whose decorations change are updated, as each update is relayed to
+    // the proxy and view.
+    fn refresh_decorations(&self) {
+        let implied = self.implied_by();
+        let entries = self.entries();
+        let diagnostics = validate_all(&self.validators.borrow(), &entries);
+        let (locked, lock_source) = {
+            let data = self.data.borrow();
+            let source = data.lock_source().unwrap_or_default().to_string();
+            (data.locked_items().len(), source)
+        };
+        let mut by_row = HashMap::<usize, Vec<&Diagnostic>>::new();
+        for diagnostic in &diagnostics {
+            by_row
+                .entry(diagnostic.row)
+                .or_default()
+                .push(&diagnostic.diagnostic);
+        }
+        unsafe {
+            let model = self.model();
+            let style = self.main.style();
+            for (row, entry) in entries.iter().enumerate() {
+                let mut si = model.item_1a(row as i32);
+                if si.is_null() {
+                    continue;
+                }
+                let key = entry.to_string();
+                let is_locked = row < locked;
+                let is_implied = implied.contains_key(&key);
+                let mut tooltip = vec![entry.tooltip()];
+                if is_locked {
+                    tooltip.push(format!("locked by {}", lock_source));
+                }
+                if let Some(implied_by) = implied.get(&key) {
+                    tooltip.push(format!("implied by {}", implied_by.join(", ")));
+                }
+                let item_diagnostics = by_row.get(&row).map_or(&[][..], Vec::as_slice);
+                tooltip.extend(item_diagnostics.iter().map(|d| d.to_string()));
+                let tooltip = tooltip.join("\n");
+                let severity = item_diagnostics.iter().map(|d| d.severity).max();
+                let decoration = Self::decoration(entry, severity, is_implied, is_locked, &tooltip);
+                if si
+                    .data_1a(Self::decoration_role())
+                    .to_string()
+                    .to_std_string()
+                    == decoration
+                {
+                    continue;
+                }
+                let mut font = si.font();
+                font.set_italic(is_implied);
+                let icon = match severity {
+                    Some(Severity::Error) => {
+                        style.standard_icon_1a(StandardPixmap::SPMessageBoxCritical)
+                    }
+                    Some(Severity::Warning) => {
+                        style.standard_icon_1a(StandardPixmap::SPMessageBoxWarning)
+                    }
+                    Some(Severity::Info) => {
+                        style.standard_icon_1a(StandardPixmap::SPMessageBoxInformation)
+                    }
+                    None if is_locked => QIcon::from_q_string(&qs(LOCK_ICON)),
+                    None => Self::item_icon(entry),
+                };
+                // locked items may be neither dragged nor disabled
+                si.set_drag_enabled(!is_locked);
+                si.set_checkable(!is_locked);
+                si.set_icon(&icon);
+                si.set_data_2a(
+                    &QVariant::from_q_string(&qs(severity.map_or("", |s| s.as_str()))),
+                    Self::diagnostic_role(),
+                );
+                si.set_font(&font);
+                si.set_tool_tip(&qs(&tooltip));
+                si.set_data_2a(
+                    &QVariant::from_q_string(&qs(&decoration)),
+                    Self::decoration_role(),
+                );
+            }
+            let severity = diagnostics
+                .iter()
+                .map(|d| d.diagnostic.severity)
+                .max()
+                .map_or("", |s| s.as_str());
+            let mut view = self.view;
+            let property = b"diagnostics\0".as_ptr() as *const c_char;
+            // stylesheets are only reapplied once the widget is repolished,
+            // which is only worthwhile when the property changes
+            if view.property(property).to_string().to_std_string() != severity {
+                view.set_property(property, &QVariant::from_q_string(&qs(severity)));
+                view.style().unpolish_q_widget(view);
+                view.style().polish_q_widget(view);
+            }
+        }
+        self.diagnostics.replace(diagnostics);
+    }
+
+    // Enable the save and revert buttons only when there are edits
+    fn update_buttons(&self) {
+        let dirty = self.is_dirty();
+        unsafe {
+            let mut save_button = self.save_button;
+            save_button.set_enabled(dirty && !self.is_save_blocked());
+            let mut revert_button = self.revert_button;
+            revert_button.set_enabled(dirty);
+        }
+    }
+
+    // Repopulate the qt model from the ItemModel. The items are built up
+    // front, and the model refilled with its signals blocked, after which
+    // the proxy is reset, so that the proxy and view are informed once,
+    // rather than once per row
+    fn rebuild_view(&self) {
+        let items = self
+            .data
+            .borrow()
+            .entries()
+            .iter()
+            .map(Self::new_item)
+            .collect::<Vec<_>>();
+        self.sync_guard(|| unsafe {
+            let mut model = self.model();
+            let blocked = model.block_signals(true);
+            let cnt = model.row_count_0a();
+            model.remove_rows_2a(0, cnt);
+            model.insert_rows_2a(0, items.len() as i32);
+            for (row, item) in items.into_iter().enumerate() {
+                model.set_item_2a(row as i32, item.into_ptr());
+            }
+            model.block_signals(blocked);
+            let mut proxy = self.proxy;
+            proxy.set_source_model(model.static_upcast_mut());
+        });
+    }
+
+    // Retrieve the item key for each row of the qt model
+    fn view_keys(&self) -> Vec<String> {
+        unsafe {
+            let sz = self.model.row_count_0a();
+            let mut rval = Vec::with_capacity(sz as usize);
+            for c in 0..sz {
+                let item = self.model.item_1a(c);
+                if item.is_null() {
+                    log::error!("item ptr is null. skipping");
+                    continue;
+                }
+                rval.push(item.data_1a(Self::key_role()).to_string().to_std_string());
+            }
+            rval
+        }
+    }
+
+    // Execute the supplied closure while flagging that we are the
+    // ones updating the qt model
+    fn sync_guard<F>(&self, func: F)
+    where
+        F: FnOnce(),
+    {
+        let prev = self.syncing.replace(true);
+        func();
+        self.syncing.set(prev);
+    }
+
+    // Update the QStandardItem at the supplied row from its entry, after it
+    // has been enabled or disabled
+    fn update_item(&self, row: usize) {
+        let entry = match self.data.borrow().get(row) {
+            Some(entry) => entry.clone(),
+            None => return,
+        };
+        unsafe {
+            let mut si = self.model().item_1a(row as i32);
+            if si.is_null() {
+                return;
+            }
+            si.set_text(&qs(entry.display_text()));
+            si.set_data_2a(
+                &QVariant::from_q_string(&qs(entry.to_string())),
+                Self::key_role(),
+            );
+            si.set_check_state(Self::check_state(&entry));
+        }
+    }
+
+    // Determine whether any of the items at the supplied rows is enabled
+    fn any_enabled(&self, rows: &[usize]) -> bool {
+        let data = self.data.borrow();
+        rows.iter()
+            .filter_map(|row| data.get(*row))
+            .any(|entry| entry.is_enabled())
+    }
+
+    // The check state displaying whether the entry is enabled
+    fn check_state(entry: &T) -> CheckState {
+        if entry.is_enabled() {
+            CheckState::Checked
+        } else {
+            CheckState::Unchecked
+        }
+    }
+
+    // The icon of the supplied entry, or an empty icon if it has none
+    fn item_icon(entry: &T) -> CppBox<QIcon> {
+        unsafe {
+            match entry.icon() {
+                Some(path) => QIcon::from_q_string(&qs(path)),
+                None => QIcon::new(),
+            }
+        }
+    }
+
+    // The data role used to store the item's key on its QStandardItem
+    fn key_role() -> i32 {
+        ItemDataRole::UserRole.to_int()
+    }
+
+    // The data role used to store the item's most severe diagnostic
+    fn diagnostic_role() -> i32 {
+        ItemDataRole::UserRole.to_int() + 1
+    }
+
+    // The data role used to store the decorations last applied to the item
+    fn decoration_role() -> i32 {
+        ItemDataRole::UserRole.to_int() + 2
+    }
+
+    // The data role used to store the item's text, without the prefix of a
+    // disabled item, against which the filter matches
+    fn item_text_role() -> i32 {
+        ItemDataRole::UserRole.to_int() + 3
+    }
+
+    // Summarise the decorations of an item, so that they need only be
+    // applied when they change
+    fn decoration(
+        entry: &T,
+        severity: Option<Severity>,
+        implied: bool,
+        locked: bool,
+        tooltip: &str,
+    ) -> String {
+        format!(
+            "{}|{}|{}|{:?}|{}",
+            severity.map_or("", |s| s.as_str()),
+            implied,
+            locked,
+            entry.icon(),
+            tooltip
+        )
+    }
+
+    // Construct a QStandardItem for the supplied entry, presented as per
+    // its ListItem implementation. The key role holds the entry's text,
+    // and the item text role its text whether or not it is disabled
+    fn new_item(entry: &T) -> CppBox<QStandardItem> {
+        unsafe {
+            let key = entry.to_string();
+            let mut si = QStandardItem::new();
+            si.set_text(&qs(entry.display_text()));
+            si.set_tool_tip(&qs(entry.tooltip()));
+            si.set_icon(&Self::item_icon(entry));
+            si.set_data_2a(&QVariant::from_q_string(&qs(&key)), Self::key_role());
+            si.set_data_2a(
+                &QVariant::from_q_string(&qs(entry.item_text())),
+                Self::item_text_role(),
+            );
+            // a new item bears the decorations of an unlocked, explicit
+            // item without diagnostics
+            let decoration = Self::decoration(entry, None, false, false, &entry.tooltip());
+            si.set_data_2a(
+                &QVariant::from_q_string(&qs(&decoration)),
+                Self::decoration_role(),
+            );
+            // the check box enables and disables the item
+            si.set_checkable(true);
+            si.set_check_state(Self::check_state(entry));
+            si.set_drop_enabled(false);
+            si
+        }
+    }
+
+    // setup the main widget, performing configuration, adding a
+    // layout, and registering ti with its parent, inserting it into
     // its parent's layout
     //
     // # Arguments
@@ -483,18 +2826,19 @@ impl InnerWithsList {
         }
     }
 
-    // Given a name and a parent, construct a QComboBox and return it
+    // Given a name and a parent, construct a QComboBox and return it, along
+    // with its label and the indicator displayed while candidates are loading
     //
     // #Arguments
     // * `name` - Name of the combobox
     // * `parent` - mut reference to the parent widget. Will be used to fetch the layout
     //
     // # Returns
-    // * A MutPtr wrapping the QComboBox
+    // * A tuple of MutPtrs wrapping the label, loading indicator, and QComboBox
     fn setup_combobox(
         name: &str,
         mut parent: &mut MutPtr<QWidget>,
-    ) -> (MutPtr<QLabel>, MutPtr<QComboBox>) {
+    ) -> (MutPtr<QLabel>, MutPtr<QLabel>, MutPtr<QComboBox>) {
         unsafe {
             let mut cb_widget = QFrame::create(&mut parent);
             cb_widget.add_layout(LayoutType::HBoxLayout);
@@ -505,6 +2849,12 @@ impl InnerWithsList {
             let cb_label_ptr = cb_label.as_mut_ptr();
             cb_widget.layout().add_widget(cb_label.into_ptr());
 
+            let mut loading_label = QLabel::from_q_string(&qs("loading…"));
+            loading_label.set_object_name(&qs("WithsLoadingLabel"));
+            loading_label.set_visible(false);
+            let loading_label_ptr = loading_label.as_mut_ptr();
+            cb_widget.layout().add_widget(loading_label.into_ptr());
+
             let mut cbox = QComboBox::new_0a();
             cbox.set_editable(true);
             cbox.set_object_name(&qs("WithsComboBox"));
@@ -514,11 +2864,63 @@ impl InnerWithsList {
             let mut layout = cb_widget.layout().dynamic_cast_mut::<QHBoxLayout>();
             if layout.is_null() {
                 log::error!("unable to cast layout to QHBoxLayout");
-                return (cb_label_ptr, cbox_ptr);
+                return (cb_label_ptr, loading_label_ptr, cbox_ptr);
             }
-            layout.set_stretch(1, 1);
+            layout.set_stretch(2, 1);
+
+            (cb_label_ptr, loading_label_ptr, cbox_ptr)
+        }
+    }
+
+    // Replace the combobox's completer with one backed by a model which we
+    // populate with fuzzy matches, so that we may complete versions as well
+    // as package names. The completer presents the model unfiltered, and
+    // completes using the key role, as highlighted completions have no
+    // display text
+    //
+    // # Arguments
+    // * `cbox` - The combobox
+    //
+    // # Returns
+    // * Tuple of MutPtrs to the completer and its model
+    fn setup_completer(
+        cbox: MutPtr<QComboBox>,
+    ) -> (MutPtr<QCompleter>, MutPtr<QStandardItemModel>) {
+        unsafe {
+            let mut cbox = cbox;
+            let mut completer = QCompleter::new_1a(cbox);
+            let completer_model = QStandardItemModel::new_1a(completer.as_mut_ptr()).into_ptr();
+            completer.set_model(completer_model);
+            completer.set_completion_mode(CompletionMode::UnfilteredPopupCompletion);
+            completer.set_completion_role(Self::key_role());
+            completer.set_case_sensitivity(CaseSensitivity::CaseInsensitive);
+            let completer = completer.into_ptr();
+            cbox.set_completer(completer);
+            (completer, completer_model)
+        }
+    }
 
-            (cb_label_ptr, cbox_ptr)
+    // construct the proxy model which sits between the model and the view,
+    // filtering the items on their keys. The proxy does not sort, so drops
+    // onto the filtered view map onto the unfiltered order of the model
+    //
+    // # Arguments
+    // * `model` - the instance of the QStandardItemModel, configured
+    // * `parent` - the widget which owns the proxy
+    //
+    // # Returns
+    // * MutPtr wrapped QSortFilterProxyModel instance
+    fn setup_proxy(
+        model: MutPtr<QStandardItemModel>,
+        parent: MutPtr<QWidget>,
+    ) -> MutPtr<QSortFilterProxyModel> {
+        unsafe {
+            let mut proxy = QSortFilterProxyModel::new_1a(parent);
+            proxy.set_source_model(model.static_upcast_mut());
+            proxy.set_filter_role(Self::item_text_role());
+            proxy.set_filter_key_column(0);
+            proxy.set_filter_case_sensitivity(CaseSensitivity::CaseInsensitive);
+            proxy.into_ptr()
         }
     }
 
@@ -526,13 +2928,13 @@ impl InnerWithsList {
     // the model, and adding it into the supplied layout
     //
     // # Arguments
-    // * `model` - the instance of the QStandardItemModel, configured
+    // * `model` - the proxy model wrapping the QStandardItemModel
     // * `layout` - The parent layout
     //
     // # Returns
     // * MutPtr wrapped QListView instance
     fn setup_listview(
-        model: MutPtr<QStandardItemModel>,
+        model: MutPtr<QSortFilterProxyModel>,
         layout: &mut MutPtr<QLayout>,
     ) -> MutPtr<QListView> {
         unsafe {
@@ -543,6 +2945,7 @@ impl InnerWithsList {
             qlv.set_selection_mode(SelectionMode::ExtendedSelection);
             qlv.set_drag_drop_overwrite_mode(false);
             qlv.set_drag_drop_mode(DragDropMode::InternalMove);
+            qlv.set_context_menu_policy(ContextMenuPolicy::CustomContextMenu);
             let qlv_ptr = qlv.as_mut_ptr();
             layout.add_widget(qlv.into_ptr());
 
@@ -550,6 +2953,25 @@ impl InnerWithsList {
         }
     }
 
+    // set up the row of buttons beneath the listview
+    //
+    // # Arguments
+    // * `parent` - mut reference to the parent widget
+    //
+    // # Returns
+    // * Tuple of the revert and save buttons
+    unsafe fn setup_buttons(
+        parent: &mut MutPtr<QWidget>,
+    ) -> (MutPtr<QPushButton>, MutPtr<QPushButton>) {
+        let mut button_widget = QFrame::create(parent);
+        button_widget.add_layout(LayoutType::HBoxLayout);
+        button_widget.set_object_name(&qs("WithsButtonWidget"));
+        let mut layout = button_widget.layout();
+        let revert_button = Self::setup_button("Revert", &mut layout);
+        let save_button = Self::setup_button("Save", &mut layout);
+        (revert_button, save_button)
+    }
+
     unsafe fn setup_button(name: &str, layout: &mut MutPtr<QLayout>) -> MutPtr<QPushButton> {
         let mut button = QPushButton::from_q_string(&qs(name));
         let button_ptr = button.as_mut_ptr();
@@ -557,3 +2979,55 @@ impl InnerWithsList {
         button_ptr
     }
 }
+
+// Files are read and written in the formats of withs, so only lists of
+// withs may import and export them
+impl InnerItemList<WithEntry> {
+    /// Replace the items with those read from a file, in the format indicated
+    /// by its extension. The comments of a text file are retained, and
+    /// written back out by `export_file`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Ok, or a PersistError
+    pub(crate) fn import_file(&self, path: &Path) -> Result<(), PersistError> {
+        // the file is validated against the rules of the list
+        let allowed = self.data.borrow().allowed_duplicates();
+        let (items, document) = match WithsFormat::from_path(path)? {
+            WithsFormat::Text => {
+                let document = TextDocument::parse_with(&fs::read_to_string(path)?, &allowed)?;
+                (document.items(), Some(document))
+            }
+            format => (
+                persist::load_as_with(path, format, &allowed)?.all_items(),
+                None,
+            ),
+        };
+        self.set_items(items)?;
+        self.document.replace(document);
+        Ok(())
+    }
+
+    /// Write the items to a file, in the format indicated by its extension.
+    /// A text file retains the comments of the last text file imported.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Ok, or a PersistError
+    pub(crate) fn export_file(&self, path: &Path) -> Result<(), PersistError> {
+        let format = WithsFormat::from_path(path)?;
+        let data = self.data.borrow();
+        match (format, self.document.borrow_mut().as_mut()) {
+            (WithsFormat::Text, Some(document)) => {
+                document.update(&data);
+                fs::write(path, document.to_string())?;
+                Ok(())
+            }
+            (format, _) => persist::save_as(&data, path, format),
+        }
+    }
+}
@@ -1,7 +1,44 @@
+pub mod bindings;
+pub use bindings::{Action, BindingError, KeyBindings, KeySequence};
+pub mod candidates;
+pub use candidates::{
+    CandidateProvider, CandidateQuery, DirectoryCandidates, FileCandidates, StaticCandidates,
+};
+pub mod change;
+pub mod clipboard;
+pub mod config;
+pub use change::WithsChange;
+pub use clipboard::{PasteRejection, PasteReport, JSON_MIME_TYPE, WITHS_MIME_TYPE};
+pub use config::{ConfigError, ListMode, SettingsFormat, UnknownListMode};
 pub mod withs_list;
-pub use withs_list::WithsList;
-pub use withs_list::WithsListConfig;
+pub use config::{ItemListConfig, WithsListConfig};
+pub use withs_list::{ItemList, WithsList};
+pub mod dependency;
+pub use dependency::{DependencyResolver, MapResolver};
+pub mod diagnostics;
+pub use diagnostics::{
+    ConflictValidator, Diagnostic, ItemDiagnostic, KnownPackagesValidator, Severity, Validator,
+};
+pub mod dnd;
+pub use dnd::PathMapper;
+pub mod filter;
+pub use filter::FilterMatch;
+pub mod fuzzy;
+pub use fuzzy::FuzzyMatch;
+pub mod handle;
+pub use handle::{ItemHandle, StaleHandle};
+pub mod history;
 pub(crate) mod inner_withs_list;
+pub mod list_item;
+pub use list_item::ListItem;
+pub(crate) mod loader;
+pub mod persist;
+pub use persist::{PersistError, WithsFormat};
+pub mod raw;
 pub(crate) mod toolbar;
 pub mod traits;
 pub mod utility;
+pub mod with_entry;
+pub use with_entry::{VersionOp, VersionSpec, WithEntry, WithEntryError, DISABLED_PREFIX};
+pub mod withs_model;
+pub use withs_model::{ItemModel, ItemModelError, WithsModel, WithsModelError};
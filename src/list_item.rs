@@ -0,0 +1,168 @@
+//! The ListItem trait, which describes how the items of a list are parsed,
+//! identified and presented.
+//!
+//! The ItemModel and ItemList are generic over the trait, and render each
+//! of their rows through it. WithEntry is the implementation for withs, from
+//! which the WithsModel and WithsList are instantiated.
+use crate::with_entry::{WithEntry, WithEntryError};
+use std::fmt;
+
+/// An item which may be displayed by the list. The Display implementation
+/// provides the item's canonical text, which `parse` must accept, and which
+/// is used when copying, dragging and saving the item.
+pub trait ListItem: Clone + fmt::Display + PartialEq + Sized + 'static {
+    /// The error returned when text cannot be parsed as an item
+    type Error: fmt::Display;
+
+    /// Parse an item from its text, as typed, pasted or dropped
+    ///
+    /// # Arguments
+    /// * `text` - The text of the item
+    ///
+    /// # Returns
+    /// * Ok wrapping the item, or an Error
+    fn parse_item(text: &str) -> Result<Self, Self::Error>;
+
+    /// Retrieve the key identifying the item. Items sharing a key are
+    /// duplicates of one another.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    fn key(&self) -> &str;
+
+    /// Retrieve the text identifying the item, whether or not it is
+    /// enabled. Items sharing a key which may appear more than once are
+    /// told apart by their text.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String
+    fn item_text(&self) -> String {
+        self.to_string()
+    }
+
+    /// Determine whether the item is enabled. Disabled items keep their
+    /// place in the list, but are left out of its items.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    /// Enable or disable the item. Items which may not be disabled ignore
+    /// the request.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether the item should be enabled
+    ///
+    /// # Returns
+    /// * None
+    fn set_enabled(&mut self, _enabled: bool) {}
+
+    /// Retrieve the text displayed for the item by the view
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String
+    fn display_text(&self) -> String {
+        self.to_string()
+    }
+
+    /// Retrieve the tooltip displayed for the item by the view
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String
+    fn tooltip(&self) -> String {
+        self.to_string()
+    }
+
+    /// Retrieve the icon displayed for the item, as a resource path or a
+    /// file path, when it has no diagnostics
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(path), or None if the item has no icon
+    fn icon(&self) -> Option<String> {
+        None
+    }
+
+    /// Retrieve the details of the item recorded, alongside its text and
+    /// key, when it is copied to the clipboard
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of field names and their values, if any
+    fn details(&self) -> Vec<(&'static str, Option<String>)> {
+        Vec::new()
+    }
+}
+
+impl ListItem for WithEntry {
+    type Error = WithEntryError;
+
+    fn parse_item(text: &str) -> Result<Self, Self::Error> {
+        Self::parse(text)
+    }
+
+    // withs are duplicates when they name the same package, whatever their
+    // version
+    fn key(&self) -> &str {
+        &self.name
+    }
+
+    // the text of a with omits the DISABLED_PREFIX
+    fn item_text(&self) -> String {
+        WithEntry::item_text(self)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    // the version constraint, if any, is displayed as a badge following the
+    // name
+    fn display_text(&self) -> String {
+        match self.version_text() {
+            Some(version) => format!("{}  [{}]", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+
+    // the version constraint is recorded in its parts
+    fn details(&self) -> Vec<(&'static str, Option<String>)> {
+        let spec = self.version_spec.as_ref();
+        vec![
+            ("op", spec.map(|spec| spec.op.as_str().to_string())),
+            ("version", spec.map(|spec| spec.version.clone())),
+        ]
+    }
+
+    fn tooltip(&self) -> String {
+        if self.enabled {
+            return self.item_text();
+        }
+        format!("{} (disabled)", self.item_text())
+    }
+}
@@ -0,0 +1,211 @@
+//! Background loading of candidates.
+//!
+//! A CandidateLoad pages through a CandidateProvider on a worker thread,
+//! sending each page back over a channel. The WithsList polls the channel
+//! from a timer on the gui thread, so that a slow provider does not freeze
+//! the gui.
+use crate::candidates::{CandidateProvider, CandidateQuery};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// An event sent from the worker thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LoadEvent {
+    /// A batch of candidates
+    Batch(Vec<String>),
+    /// All of the candidates have been sent
+    Finished,
+    /// The provider failed. Holds the error message
+    Failed(String),
+}
+
+/// A load of candidates in progress. Dropping the load cancels it.
+pub(crate) struct CandidateLoad {
+    receiver: Receiver<LoadEvent>,
+    cancelled: Arc<AtomicBool>,
+    finished: Cell<bool>,
+}
+
+impl CandidateLoad {
+    /// Start loading all of the candidates of the provider on a worker thread
+    ///
+    /// # Arguments
+    /// * `provider` - The CandidateProvider to load from
+    /// * `batch_size` - The number of candidates requested per page
+    ///
+    /// # Returns
+    /// * CandidateLoad instance
+    pub(crate) fn start(provider: Arc<dyn CandidateProvider>, batch_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            let mut query = CandidateQuery::new("", batch_size.max(1));
+            while !worker_cancelled.load(Ordering::Relaxed) {
+                match provider.query(&query) {
+                    Ok(page) => {
+                        let has_more = page.has_more;
+                        // a failed send means that the load has been dropped
+                        if sender.send(LoadEvent::Batch(page.candidates)).is_err() {
+                            break;
+                        }
+                        if !has_more {
+                            let _ = sender.send(LoadEvent::Finished);
+                            break;
+                        }
+                        query = query.next_page();
+                    }
+                    Err(e) => {
+                        let _ = sender.send(LoadEvent::Failed(e.to_string()));
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            receiver,
+            cancelled,
+            finished: Cell::new(false),
+        }
+    }
+
+    /// Retrieve up to `max` of the events which have arrived, without
+    /// blocking. Once the load is cancelled, or has finished or failed, no
+    /// further events are returned.
+    ///
+    /// # Arguments
+    /// * `max` - The maximum number of events to retrieve
+    ///
+    /// # Returns
+    /// * Vector of LoadEvents
+    pub(crate) fn poll(&self, max: usize) -> Vec<LoadEvent> {
+        let mut events = Vec::new();
+        while events.len() < max && !self.is_cancelled() && !self.finished.get() {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    if let LoadEvent::Finished | LoadEvent::Failed(_) = event {
+                        self.finished.set(true);
+                    }
+                    events.push(event);
+                }
+                Err(TryRecvError::Empty) => break,
+                // the worker exited without finishing, as it does if it panics
+                Err(TryRecvError::Disconnected) => {
+                    self.finished.set(true);
+                    events.push(LoadEvent::Failed("candidate loader exited".to_string()));
+                }
+            }
+        }
+        events
+    }
+
+    /// Cancel the load. The worker thread stops once it has finished the
+    /// page it is working on.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Determine whether the load has been cancelled
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for CandidateLoad {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candidates::{CandidateError, CandidatePage, StaticCandidates};
+    use std::time::{Duration, Instant};
+
+    // a provider which always fails
+    struct Unreachable;
+
+    impl CandidateProvider for Unreachable {
+        fn query(&self, _query: &CandidateQuery) -> Result<CandidatePage, CandidateError> {
+            Err("the repository is unreachable".into())
+        }
+    }
+
+    // poll the load, one event at a time, until it finishes or fails
+    fn events(load: &CandidateLoad) -> Vec<LoadEvent> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut events = Vec::new();
+        while !matches!(
+            events.last(),
+            Some(LoadEvent::Finished) | Some(LoadEvent::Failed(_))
+        ) {
+            assert!(Instant::now() < deadline, "the load did not finish");
+            let polled = load.poll(1);
+            assert!(polled.len() <= 1);
+            if polled.is_empty() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            events.extend(polled);
+        }
+        events
+    }
+
+    fn batch(candidates: &[&str]) -> LoadEvent {
+        LoadEvent::Batch(candidates.iter().map(|c| c.to_string()).collect())
+    }
+
+    #[test]
+    fn candidates_arrive_a_page_at_a_time() {
+        let provider = StaticCandidates::new(vec!["houdini", "mari", "maya", "nuke", "vray"]);
+        let load = CandidateLoad::start(Arc::new(provider), 2);
+        assert_eq!(
+            events(&load),
+            vec![
+                batch(&["houdini", "mari"]),
+                batch(&["maya", "nuke"]),
+                batch(&["vray"]),
+                LoadEvent::Finished,
+            ]
+        );
+        // nothing follows the end of the load
+        assert!(load.poll(10).is_empty());
+    }
+
+    #[test]
+    fn failures_end_the_load() {
+        let load = CandidateLoad::start(Arc::new(Unreachable), 2);
+        assert_eq!(
+            events(&load),
+            vec![LoadEvent::Failed(
+                "the repository is unreachable".to_string()
+            )]
+        );
+        assert!(load.poll(10).is_empty());
+    }
+
+    #[test]
+    fn cancelled_loads_return_nothing() {
+        let provider = StaticCandidates::new(vec!["houdini", "maya"]);
+        let load = CandidateLoad::start(Arc::new(provider), 1);
+        assert!(!load.is_cancelled());
+        load.cancel();
+        assert!(load.is_cancelled());
+        assert!(load.poll(10).is_empty());
+    }
+}
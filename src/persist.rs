@@ -0,0 +1,795 @@
+//! Reading and writing withs lists.
+//!
+//! Three formats are supported:
+//! * Text - one with per line. Lines starting with `#` are comments, and
+//!   along with blank lines, are preserved when a list is written back out
+//!   via a TextDocument. Disabled withs are commented out with the
+//!   DISABLED_PREFIX (eg `#~houdini`). Lines bearing the prefix which do not
+//!   hold a with (eg `#~~~~ header ~~~~`) remain comments.
+//! * Json - an array of strings
+//! * Yaml - a sequence of strings
+//!
+//! In json and yaml, disabled withs are written as a table holding the item
+//! and an enabled flag (eg `{"item": "houdini", "enabled": false}`), while
+//! enabled withs remain strings.
+//!
+//! Each item is validated as a WithEntry, and the list as a whole must be
+//! free of duplicates, so anything which is read may be written back out
+//! unchanged. Lists which allow some packages to appear more than once are
+//! read via the `_with` variants of the functions (eg `load_with`), which
+//! take the packages allowed to be duplicated.
+use crate::with_entry::{WithEntry, WithEntryError, DISABLED_PREFIX};
+use crate::withs_model::{WithsModel, WithsModelError};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Errors returned when reading or writing a withs list
+#[derive(Debug)]
+pub enum PersistError {
+    /// Unable to read or write the file
+    Io(io::Error),
+    /// The format could not be determined from the file's extension
+    UnknownFormat(PathBuf),
+    /// A line of a text document is not a valid with
+    InvalidLine { line: usize, error: WithEntryError },
+    /// A json document is malformed, or is not an array
+    Json(serde_json::Error),
+    /// A yaml document is malformed, or is not a sequence
+    Yaml(serde_yaml::Error),
+    /// An item of a json or yaml document is not a valid with
+    InvalidItem { index: usize, error: WithEntryError },
+    /// An item of a json or yaml document is neither a string nor a table
+    /// holding an item and an enabled flag
+    UnexpectedItem(usize),
+    /// The items are not a valid withs list (eg there are duplicates)
+    Model(WithsModelError),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::UnknownFormat(path) => {
+                write!(f, "unable to determine the format of '{}'", path.display())
+            }
+            Self::InvalidLine { line, error } => write!(f, "line {}: {}", line, error),
+            Self::Json(e) => write!(f, "malformed json: {}", e),
+            Self::Yaml(e) => write!(f, "malformed yaml: {}", e),
+            Self::InvalidItem { index, error } => write!(f, "item {}: {}", index, error),
+            Self::UnexpectedItem(index) => write!(
+                f,
+                "item {}: expected a string, or a table holding an item and an enabled flag",
+                index
+            ),
+            Self::Model(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for PersistError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::InvalidLine { error, .. } | Self::InvalidItem { error, .. } => Some(error),
+            Self::Json(e) => Some(e),
+            Self::Yaml(e) => Some(e),
+            Self::Model(e) => Some(e),
+            Self::UnknownFormat(_) | Self::UnexpectedItem(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<WithsModelError> for PersistError {
+    fn from(e: WithsModelError) -> Self {
+        Self::Model(e)
+    }
+}
+
+/// The format of a withs list file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithsFormat {
+    /// Newline delimited text
+    Text,
+    /// A json array
+    Json,
+    /// A yaml sequence
+    Yaml,
+}
+
+impl WithsFormat {
+    /// Determine the format from a path's extension. `.json` is Json,
+    /// `.yaml` and `.yml` are Yaml, and `.txt` and `.withs` are Text.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Ok wrapping the WithsFormat, or UnknownFormat
+    pub fn from_path(path: &Path) -> Result<Self, PersistError> {
+        match path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("json") => Ok(Self::Json),
+            Some("yaml") | Some("yml") => Ok(Self::Yaml),
+            Some("txt") | Some("withs") => Ok(Self::Text),
+            _ => Err(PersistError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+/// A line of a text document
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    Blank(String),
+    Comment(String),
+    Item(String, WithEntry),
+}
+
+// an item of a text document, along with its original line and the lines
+// preceding it
+type Block = (Vec<Line>, String, WithEntry);
+
+/// A newline delimited withs list which retains its comments and blank
+/// lines, so that it may be updated without losing them.
+///
+/// Comments are attached to the with which follows them, and travel with it
+/// when the items are reordered. Comments which are separated from the first
+/// with by a blank line are treated as a header, and remain at the top of the
+/// document.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextDocument {
+    lines: Vec<Line>,
+}
+
+impl TextDocument {
+    /// Parse a text document
+    ///
+    /// # Arguments
+    /// * `input` - The text of the document
+    ///
+    /// # Returns
+    /// * Ok wrapping the TextDocument, or an InvalidLine or Model error
+    pub fn parse(input: &str) -> Result<Self, PersistError> {
+        Self::parse_with(input, &[])
+    }
+
+    /// Parse a text document, allowing the supplied packages to appear more
+    /// than once
+    ///
+    /// # Arguments
+    /// * `input` - The text of the document
+    /// * `allowed_duplicates` - The packages which may appear more than once
+    ///
+    /// # Returns
+    /// * Ok wrapping the TextDocument, or an InvalidLine or Model error
+    pub fn parse_with(input: &str, allowed_duplicates: &[String]) -> Result<Self, PersistError> {
+        let mut lines = Vec::new();
+        for (idx, text) in input.lines().enumerate() {
+            let trimmed = text.trim();
+            let line = if trimmed.is_empty() {
+                Line::Blank(text.to_string())
+            } else if trimmed.starts_with(DISABLED_PREFIX) {
+                // a disabled with, unless the remainder is not a with
+                match WithEntry::parse(trimmed) {
+                    Ok(entry) => Line::Item(text.to_string(), entry),
+                    Err(_) => Line::Comment(text.to_string()),
+                }
+            } else if trimmed.starts_with('#') {
+                Line::Comment(text.to_string())
+            } else {
+                let entry =
+                    WithEntry::parse(trimmed).map_err(|error| PersistError::InvalidLine {
+                        line: idx + 1,
+                        error,
+                    })?;
+                Line::Item(text.to_string(), entry)
+            };
+            lines.push(line);
+        }
+        let document = Self { lines };
+        // validate the items as a whole
+        WithsModel::from_items_with(document.items(), allowed_duplicates.to_vec())?;
+        Ok(document)
+    }
+
+    /// Retrieve the items of the document, in order. Disabled items carry
+    /// the DISABLED_PREFIX.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items
+    pub fn items(&self) -> Vec<String> {
+        self.entries().map(WithEntry::to_string).collect()
+    }
+
+    /// Replace the items of the document, retaining the comments attached
+    /// to any items which remain. An item retains its original line as long
+    /// as it is unchanged. Each item keeps the comments of an identical item
+    /// (whether or not it is enabled), or failing that, of an item of the
+    /// same package, so that a package appearing more than once keeps the
+    /// comments of each appearance.
+    ///
+    /// # Arguments
+    /// * `model` - The withs supplying the new items
+    ///
+    /// # Returns
+    /// * None
+    pub fn update(&mut self, model: &WithsModel) {
+        let lines = std::mem::take(&mut self.lines);
+        let first_item = lines
+            .iter()
+            .position(|line| matches!(line, Line::Item(..)))
+            .unwrap_or(lines.len());
+        // the header extends through the last blank line preceding the first item
+        let header_len = lines[..first_item]
+            .iter()
+            .rposition(|line| matches!(line, Line::Blank(_)))
+            .map_or(0, |idx| idx + 1);
+        let mut lines = lines.into_iter();
+        let mut updated = lines.by_ref().take(header_len).collect::<Vec<_>>();
+
+        // gather the comments preceding each item, along with the blocks
+        // of each item and package, in order
+        let mut blocks = Vec::new();
+        let mut by_text = HashMap::<String, VecDeque<usize>>::new();
+        let mut by_name = HashMap::<String, VecDeque<usize>>::new();
+        let mut pending = Vec::new();
+        for line in lines {
+            match line {
+                Line::Item(text, entry) => {
+                    by_text
+                        .entry(entry.item_text())
+                        .or_default()
+                        .push_back(blocks.len());
+                    by_name
+                        .entry(entry.name.clone())
+                        .or_default()
+                        .push_back(blocks.len());
+                    blocks.push(Some((std::mem::take(&mut pending), text, entry)));
+                }
+                line => pending.push(line),
+            }
+        }
+        // identical items claim their blocks before any item claims the
+        // block of another version of its package
+        let entries = model.entries();
+        let mut claimed = entries
+            .iter()
+            .map(|entry| Self::claim(&mut blocks, &mut by_text, &entry.item_text()))
+            .collect::<Vec<_>>();
+        for (entry, block) in entries.iter().zip(claimed.iter_mut()) {
+            if block.is_none() {
+                *block = Self::claim(&mut blocks, &mut by_name, &entry.name);
+            }
+        }
+        for (entry, block) in entries.iter().zip(claimed) {
+            match block {
+                Some((comments, text, old)) => {
+                    // avoid doubling up blank lines when the item moves to the top
+                    let separated = updated
+                        .last()
+                        .is_none_or(|line| matches!(line, Line::Blank(_)));
+                    updated.extend(
+                        comments
+                            .into_iter()
+                            .skip_while(|line| separated && matches!(line, Line::Blank(_))),
+                    );
+                    let text = if old == *entry {
+                        text
+                    } else {
+                        entry.to_string()
+                    };
+                    updated.push(Line::Item(text, entry.clone()));
+                }
+                None => updated.push(Line::Item(entry.to_string(), entry.clone())),
+            }
+        }
+        // trailing comments remain at the end
+        updated.extend(pending);
+        self.lines = updated;
+    }
+
+    // take the first unclaimed block recorded against the key
+    fn claim(
+        blocks: &mut [Option<Block>],
+        keyed: &mut HashMap<String, VecDeque<usize>>,
+        key: &str,
+    ) -> Option<Block> {
+        let indices = keyed.get_mut(key)?;
+        while let Some(idx) = indices.pop_front() {
+            if let Some(block) = blocks[idx].take() {
+                return Some(block);
+            }
+        }
+        None
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &WithEntry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Item(_, entry) => Some(entry),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for TextDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Blank(text) | Line::Comment(text) | Line::Item(text, _) => {
+                    writeln!(f, "{}", text)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read withs from newline delimited text, skipping comments and blank lines
+///
+/// # Arguments
+/// * `input` - The text
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_text(input: &str) -> Result<WithsModel, PersistError> {
+    from_text_with(input, &[])
+}
+
+/// Read withs from newline delimited text, as per `from_text`, allowing the
+/// supplied packages to appear more than once
+///
+/// # Arguments
+/// * `input` - The text
+/// * `allowed_duplicates` - The packages which may appear more than once
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_text_with(
+    input: &str,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let document = TextDocument::parse_with(input, allowed_duplicates)?;
+    Ok(WithsModel::from_items_with(
+        document.items(),
+        allowed_duplicates.to_vec(),
+    )?)
+}
+
+/// Write withs as newline delimited text, commenting out those which are
+/// disabled
+///
+/// # Arguments
+/// * `model` - The withs
+///
+/// # Returns
+/// * The text, with a trailing newline
+pub fn to_text(model: &WithsModel) -> String {
+    model
+        .all_items()
+        .iter()
+        .map(|item| format!("{}\n", item))
+        .collect()
+}
+
+/// Read withs from a json array of strings
+///
+/// # Arguments
+/// * `input` - The json
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_json(input: &str) -> Result<WithsModel, PersistError> {
+    from_json_with(input, &[])
+}
+
+/// Read withs from a json array, as per `from_json`, allowing the supplied
+/// packages to appear more than once
+///
+/// # Arguments
+/// * `input` - The json
+/// * `allowed_duplicates` - The packages which may appear more than once
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_json_with(
+    input: &str,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let items = serde_json::from_str::<Vec<Value>>(input).map_err(PersistError::Json)?;
+    from_values(items, allowed_duplicates)
+}
+
+/// Write withs as a json array of strings
+///
+/// # Arguments
+/// * `model` - The withs
+///
+/// # Returns
+/// * Ok wrapping the json, or a Json error
+pub fn to_json(model: &WithsModel) -> Result<String, PersistError> {
+    serde_json::to_string_pretty(&to_values(model)).map_err(PersistError::Json)
+}
+
+/// Read withs from a yaml sequence of strings
+///
+/// # Arguments
+/// * `input` - The yaml
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_yaml(input: &str) -> Result<WithsModel, PersistError> {
+    from_yaml_with(input, &[])
+}
+
+/// Read withs from a yaml sequence, as per `from_yaml`, allowing the
+/// supplied packages to appear more than once
+///
+/// # Arguments
+/// * `input` - The yaml
+/// * `allowed_duplicates` - The packages which may appear more than once
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn from_yaml_with(
+    input: &str,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let items = serde_yaml::from_str::<Vec<Value>>(input).map_err(PersistError::Yaml)?;
+    from_values(items, allowed_duplicates)
+}
+
+/// Write withs as a yaml sequence of strings
+///
+/// # Arguments
+/// * `model` - The withs
+///
+/// # Returns
+/// * Ok wrapping the yaml, or a Yaml error
+pub fn to_yaml(model: &WithsModel) -> Result<String, PersistError> {
+    serde_yaml::to_string(&to_values(model)).map_err(PersistError::Yaml)
+}
+
+/// Read withs from a file, in the format indicated by its extension
+///
+/// # Arguments
+/// * `path` - The path of the file
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn load<P: AsRef<Path>>(path: P) -> Result<WithsModel, PersistError> {
+    load_with(path, &[])
+}
+
+/// Read withs from a file, in the format indicated by its extension,
+/// allowing the supplied packages to appear more than once
+///
+/// # Arguments
+/// * `path` - The path of the file
+/// * `allowed_duplicates` - The packages which may appear more than once
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn load_with<P: AsRef<Path>>(
+    path: P,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let path = path.as_ref();
+    load_as_with(path, WithsFormat::from_path(path)?, allowed_duplicates)
+}
+
+/// Read withs from a file in the supplied format
+///
+/// # Arguments
+/// * `path` - The path of the file
+/// * `format` - The format of the file
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn load_as<P: AsRef<Path>>(path: P, format: WithsFormat) -> Result<WithsModel, PersistError> {
+    load_as_with(path, format, &[])
+}
+
+/// Read withs from a file in the supplied format, allowing the supplied
+/// packages to appear more than once
+///
+/// # Arguments
+/// * `path` - The path of the file
+/// * `format` - The format of the file
+/// * `allowed_duplicates` - The packages which may appear more than once
+///
+/// # Returns
+/// * Ok wrapping the WithsModel, or a PersistError
+pub fn load_as_with<P: AsRef<Path>>(
+    path: P,
+    format: WithsFormat,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let input = fs::read_to_string(path)?;
+    match format {
+        WithsFormat::Text => from_text_with(&input, allowed_duplicates),
+        WithsFormat::Json => from_json_with(&input, allowed_duplicates),
+        WithsFormat::Yaml => from_yaml_with(&input, allowed_duplicates),
+    }
+}
+
+/// Write withs to a file, in the format indicated by its extension
+///
+/// # Arguments
+/// * `model` - The withs
+/// * `path` - The path of the file
+///
+/// # Returns
+/// * Ok, or a PersistError
+pub fn save<P: AsRef<Path>>(model: &WithsModel, path: P) -> Result<(), PersistError> {
+    let path = path.as_ref();
+    save_as(model, path, WithsFormat::from_path(path)?)
+}
+
+/// Write withs to a file in the supplied format
+///
+/// # Arguments
+/// * `model` - The withs
+/// * `path` - The path of the file
+/// * `format` - The format of the file
+///
+/// # Returns
+/// * Ok, or a PersistError
+pub fn save_as<P: AsRef<Path>>(
+    model: &WithsModel,
+    path: P,
+    format: WithsFormat,
+) -> Result<(), PersistError> {
+    let output = match format {
+        WithsFormat::Text => to_text(model),
+        WithsFormat::Json => to_json(model)?,
+        WithsFormat::Yaml => to_yaml(model)?,
+    };
+    fs::write(path, output)?;
+    Ok(())
+}
+
+// the json or yaml values of the entries. Disabled entries are written as
+// tables, so that they remain readable by tools unaware of the prefix
+fn to_values(model: &WithsModel) -> Vec<Value> {
+    model
+        .entries()
+        .iter()
+        .map(|entry| {
+            if entry.enabled {
+                return Value::from(entry.to_string());
+            }
+            json!({ "item": entry.item_text(), "enabled": false })
+        })
+        .collect()
+}
+
+// validate each of the items, reporting the index of the first invalid item
+fn from_values(
+    items: Vec<Value>,
+    allowed_duplicates: &[String],
+) -> Result<WithsModel, PersistError> {
+    let entries = items
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let (item, enabled) = match value {
+                Value::String(item) => (item.as_str(), true),
+                Value::Object(table) => (
+                    table
+                        .get("item")
+                        .and_then(Value::as_str)
+                        .ok_or(PersistError::UnexpectedItem(index))?,
+                    match table.get("enabled") {
+                        None => true,
+                        Some(enabled) => enabled
+                            .as_bool()
+                            .ok_or(PersistError::UnexpectedItem(index))?,
+                    },
+                ),
+                _ => return Err(PersistError::UnexpectedItem(index)),
+            };
+            let mut entry = WithEntry::parse(item)
+                .map_err(|error| PersistError::InvalidItem { index, error })?;
+            entry.enabled &= enabled;
+            Ok(entry)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(WithsModel::from_items_with(
+        entries.iter().map(WithEntry::to_string).collect(),
+        allowed_duplicates.to_vec(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed() -> Vec<String> {
+        vec!["maya".to_string()]
+    }
+
+    fn duplicated() -> WithsModel {
+        WithsModel::from_items_with(vec!["maya-2019", "maya-2020", "houdini"], allowed())
+            .expect("maya may be duplicated")
+    }
+
+    #[test]
+    fn allowed_duplicates_round_trip_through_text() {
+        let model = duplicated();
+        let text = to_text(&model);
+        assert!(matches!(
+            from_text(&text),
+            Err(PersistError::Model(WithsModelError::Duplicate(_)))
+        ));
+        let loaded = from_text_with(&text, &allowed()).unwrap();
+        assert_eq!(loaded.items(), model.items());
+        let document = TextDocument::parse_with(&text, &allowed()).unwrap();
+        assert_eq!(document.items(), model.items());
+    }
+
+    #[test]
+    fn allowed_duplicates_round_trip_through_json_and_yaml() {
+        let model = duplicated();
+        let json = to_json(&model).unwrap();
+        assert!(from_json(&json).is_err());
+        assert_eq!(
+            from_json_with(&json, &allowed()).unwrap().items(),
+            model.items()
+        );
+        let yaml = to_yaml(&model).unwrap();
+        assert!(from_yaml(&yaml).is_err());
+        assert_eq!(
+            from_yaml_with(&yaml, &allowed()).unwrap().items(),
+            model.items()
+        );
+    }
+
+    const DOCUMENT: &str = "\
+# the show's withs
+# maintained by the pipeline
+
+# the renderer
+houdini-18.0.348
+maya>=2020
+
+# retired for now
+#~nuke-12.0
+katana
+# trailing comment
+";
+
+    #[test]
+    fn text_documents_round_trip_unchanged() {
+        let document = TextDocument::parse(DOCUMENT).unwrap();
+        assert_eq!(
+            document.items(),
+            vec!["houdini-18.0.348", "maya>=2020", "#~nuke-12.0", "katana"]
+        );
+        assert_eq!(document.to_string(), DOCUMENT);
+        let model = from_text(DOCUMENT).unwrap();
+        assert_eq!(
+            model.items(),
+            vec!["houdini-18.0.348", "maya>=2020", "katana"]
+        );
+        let reloaded = from_text(&to_text(&model)).unwrap();
+        assert_eq!(reloaded.entries(), model.entries());
+    }
+
+    #[test]
+    fn prefixed_lines_which_are_not_withs_remain_comments() {
+        let text = "#~~~~ header ~~~~\nhoudini\n#~maya-2020\n#~~~~ footer ~~~~\n";
+        let document = TextDocument::parse(text).unwrap();
+        assert_eq!(document.items(), vec!["houdini", "#~maya-2020"]);
+        assert_eq!(document.to_string(), text);
+        let model = from_text(text).unwrap();
+        assert_eq!(model.all_items(), vec!["houdini", "#~maya-2020"]);
+        assert_eq!(from_text(&to_text(&model)).unwrap(), model);
+    }
+
+    #[test]
+    fn updated_documents_keep_comments_with_their_items() {
+        let mut document = TextDocument::parse(DOCUMENT).unwrap();
+        let model =
+            WithsModel::from_items(vec!["katana", "#~maya>=2020", "nuke-12.0", "mari"]).unwrap();
+        document.update(&model);
+        assert_eq!(
+            document.to_string(),
+            "\
+# the show's withs
+# maintained by the pipeline
+
+katana
+#~maya>=2020
+
+# retired for now
+nuke-12.0
+mari
+# trailing comment
+"
+        );
+        let reloaded = TextDocument::parse(&document.to_string()).unwrap();
+        assert_eq!(reloaded.items(), model.all_items());
+    }
+
+    #[test]
+    fn updated_documents_keep_the_comments_of_each_duplicate() {
+        let text = "# old maya\nmaya-2019\n# new maya\nmaya-2020\n";
+        let mut document = TextDocument::parse_with(text, &allowed()).unwrap();
+        let model = WithsModel::from_items_with(vec!["maya-2020", "maya-2021"], allowed()).unwrap();
+        document.update(&model);
+        assert_eq!(
+            document.to_string(),
+            "# new maya\nmaya-2020\n# old maya\nmaya-2021\n"
+        );
+    }
+
+    #[test]
+    fn json_and_yaml_round_trip_disabled_items() {
+        let model = WithsModel::from_items(vec!["houdini-18.0", "#~maya>=2020", "nuke"]).unwrap();
+        let json = to_json(&model).unwrap();
+        assert!(json.contains(r#""enabled": false"#));
+        assert_eq!(from_json(&json).unwrap().entries(), model.entries());
+        let yaml = to_yaml(&model).unwrap();
+        assert_eq!(from_yaml(&yaml).unwrap().entries(), model.entries());
+    }
+
+    #[test]
+    fn files_round_trip_in_each_format() {
+        let dir = std::env::temp_dir().join(format!("pbgui-withs-persist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = WithsModel::from_items(vec!["houdini-18.0", "#~maya>=2020", "nuke"]).unwrap();
+        for name in &["withs.txt", "withs.json", "withs.yaml"] {
+            let path = dir.join(name);
+            save(&model, &path).unwrap();
+            assert_eq!(load(&path).unwrap().entries(), model.entries(), "{}", name);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_input_is_reported() {
+        assert!(matches!(
+            from_text("houdini\nmaya >= 2020\n"),
+            Err(PersistError::InvalidLine { line: 2, .. })
+        ));
+        assert!(matches!(from_json("{}"), Err(PersistError::Json(_))));
+        assert!(matches!(
+            from_json(r#"["houdini", "maya >= 2020"]"#),
+            Err(PersistError::InvalidItem { index: 1, .. })
+        ));
+        assert!(matches!(
+            from_json(r#"["houdini", 1]"#),
+            Err(PersistError::UnexpectedItem(1))
+        ));
+        assert!(matches!(
+            from_yaml("houdini: 1"),
+            Err(PersistError::Yaml(_))
+        ));
+        assert!(matches!(
+            from_yaml("- houdini\n- houdini\n"),
+            Err(PersistError::Model(WithsModelError::Duplicate(_)))
+        ));
+        assert!(matches!(
+            load("withs.csv"),
+            Err(PersistError::UnknownFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Escape hatch to the qt objects underlying a WithsList.
+//!
+//! The WithsList's own api deals in plain rust types. Where that is not
+//! enough, such as when styling or restyling a particular widget, the
+//! `RawWithsList` trait exposes the underlying qt objects, once imported.
+//! The pointers it returns are owned by the WithsList, and must not be used
+//! once it has been dropped, nor used to change the items of the model
+//! behind the WithsList's back, which would leave the WithsList unaware of
+//! the change.
+//!
+//! ```ignore
+//! use pbgui_withs::raw::RawWithsList;
+//!
+//! let mut view = withs_list.view();
+//! unsafe { view.set_alternating_row_colors(true) };
+//! ```
+use crate::list_item::ListItem;
+use crate::withs_list::ItemList;
+use qt_core::{QModelIndex, QString};
+use qt_gui::{QStandardItem, QStandardItemModel};
+use qt_widgets::{cpp_core::MutPtr, cpp_core::Ref as QRef, QListView, QPushButton, QWidget};
+
+/// Provides access to the qt objects underlying a WithsList, or any other
+/// ItemList
+pub trait RawWithsList {
+    /// Retrieve a mutable pointer to the component's top QWidget. That is
+    /// the widget contained within that is the parent of the other internal
+    /// widgets.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Mutable Pointer to the main QWidget
+    fn main(&self) -> MutPtr<QWidget>;
+
+    /// Retrieve the model for the component
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr wrapping QStandardItemModel
+    fn model(&self) -> MutPtr<QStandardItemModel>;
+
+    /// Retrieve the primary list view.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr to QListView
+    fn view(&self) -> MutPtr<QListView>;
+
+    /// Retrieve a MutPtr to the save button
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr to QPushButton
+    fn save_button(&self) -> MutPtr<QPushButton>;
+
+    /// Retrieve a MutPtr to the revert button
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MutPtr to QPushButton
+    fn revert_button(&self) -> MutPtr<QPushButton>;
+
+    /// Retrieve the model's item for the item with the supplied name
+    ///
+    /// # Arguments
+    /// * `item` - The item to be found, as a Ref wrapped QString
+    ///
+    /// # Returns
+    /// * Some(MutPtr to the QStandardItem), or None if it is not in the list
+    fn find_q_item(&self, item: QRef<QString>) -> Option<MutPtr<QStandardItem>>;
+
+    /// Scroll to the provided item in the list
+    ///
+    /// # Arguments
+    /// * `item` - A Ref wrapped QString.
+    /// * `select_item` - Whether the item should be selected as well as
+    ///   centered in the view
+    ///
+    /// # Returns
+    /// * true if an item was found, false otherwise
+    fn scroll_to_q_item(&self, item: QRef<QString>, select_item: bool) -> bool;
+
+    /// Select the item at the supplied index of the model
+    ///
+    /// # Arguments
+    /// * `index` - QModelIndex of the item, within the model
+    ///
+    /// # Returns
+    /// * None
+    fn select_q_index(&self, index: QRef<QModelIndex>);
+}
+
+impl<'l, T: ListItem> RawWithsList for ItemList<'l, T> {
+    fn main(&self) -> MutPtr<QWidget> {
+        self.inner().main()
+    }
+
+    fn model(&self) -> MutPtr<QStandardItemModel> {
+        self.inner().model()
+    }
+
+    fn view(&self) -> MutPtr<QListView> {
+        self.inner().view()
+    }
+
+    fn save_button(&self) -> MutPtr<QPushButton> {
+        self.inner().save_button()
+    }
+
+    fn revert_button(&self) -> MutPtr<QPushButton> {
+        self.inner().revert_button()
+    }
+
+    fn find_q_item(&self, item: QRef<QString>) -> Option<MutPtr<QStandardItem>> {
+        self.inner().find_item(item)
+    }
+
+    fn scroll_to_q_item(&self, item: QRef<QString>, select_item: bool) -> bool {
+        self.inner().scroll_to_item(item, select_item)
+    }
+
+    fn select_q_index(&self, index: QRef<QModelIndex>) {
+        self.inner().select_item(index);
+    }
+}
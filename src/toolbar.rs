@@ -23,6 +23,7 @@ pub struct ItemListModeToolbar {
     pub action_group: MutPtr<QActionGroup>,
     pub add_mode_action: MutPtr<QAction>,
     pub find_mode_action: MutPtr<QAction>,
+    pub filter_mode_action: MutPtr<QAction>,
     _mode_icon: CppBox<QIcon>,
 }
 
@@ -75,21 +76,29 @@ impl ItemListModeToolbar {
                 Some(mode_icon.as_ref()),
             );
 
+            // Filter
+            let (filter_mode_action, _filter_button_ref) = Self::create_mode_action(
+                "Filter",
+                action_group_ptr,
+                &mut toolbar.as_mut_ptr(),
+                false,
+                Some(mode_icon.as_ref()),
+            );
+
             // add in spacer
             toolbar.add_widget(spacer.into_ptr());
 
             let toolbar_ptr = toolbar.as_mut_ptr();
             parent.layout().add_widget(toolbar.into_ptr());
 
-            let tb = Self {
+            Self {
                 toolbar: toolbar_ptr,
                 action_group: action_group.into_ptr(),
                 find_mode_action: find_mode_action.into_ptr(),
                 add_mode_action: add_mode_action.into_ptr(),
+                filter_mode_action: filter_mode_action.into_ptr(),
                 _mode_icon: mode_icon,
-            };
-
-            tb
+            }
         }
     }
 
@@ -120,6 +129,18 @@ impl ItemListModeToolbar {
         unsafe { self.add_mode_action.is_checked() }
     }
 
+    #[allow(dead_code)]
+    /// Determine whether the filter mode is active
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether or not the filter mode is active
+    pub fn is_filter_active(&self) -> bool {
+        unsafe { self.filter_mode_action.is_checked() }
+    }
+
     // Create and configure the QToolBar internal instance, provided a name
     //
     // # Arguments
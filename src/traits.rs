@@ -42,6 +42,10 @@ pub enum LayoutType {
 }
 
 /// Trait provides a function to add a layout to
+///
+/// # Safety
+/// Implementors must point at a live widget, which takes ownership of the
+/// layout
 pub unsafe trait AddLayout<R> {
     type Layout;
     fn add_layout(&mut self, layout: Self::Layout) -> MutPtr<R>;
@@ -0,0 +1,449 @@
+//! Structured with entries.
+//!
+//! A with is a package name, optionally followed by a version constraint.
+//! A version may either be pinned with a dash (eg `houdini-18.0.348`), or
+//! constrained with a comparison operator (eg `maya>=2020`).
+//!
+//! A with may also be disabled, which keeps its place in the list while
+//! leaving it out of the list's items. Disabled withs are written with the
+//! DISABLED_PREFIX (eg `#~houdini-18.0.348`).
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The prefix marking a disabled with
+pub const DISABLED_PREFIX: &str = "#~";
+
+/// Errors returned when parsing a WithEntry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WithEntryError {
+    /// The entry is empty
+    Empty,
+    /// The package name is invalid
+    InvalidName(String),
+    /// The version constraint is invalid
+    InvalidVersion(String),
+}
+
+impl fmt::Display for WithEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "with entry is empty"),
+            Self::InvalidName(name) => write!(f, "'{}' is not a valid package name", name),
+            Self::InvalidVersion(version) => {
+                write!(f, "'{}' is not a valid version constraint", version)
+            }
+        }
+    }
+}
+
+impl Error for WithEntryError {}
+
+/// The comparison applied by a VersionSpec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionOp {
+    /// Pinned to the version, written `name-version`
+    Pin,
+    /// Exactly the version, written `name==version`
+    Eq,
+    /// Greater than or equal to the version
+    Ge,
+    /// Greater than the version
+    Gt,
+    /// Less than or equal to the version
+    Le,
+    /// Less than the version
+    Lt,
+}
+
+impl VersionOp {
+    // operators in the order in which they must be matched, longest first
+    const OPERATORS: [(&'static str, VersionOp); 5] = [
+        ("==", VersionOp::Eq),
+        (">=", VersionOp::Ge),
+        ("<=", VersionOp::Le),
+        (">", VersionOp::Gt),
+        ("<", VersionOp::Lt),
+    ];
+
+    /// Retrieve the textual form of the operator
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * &str
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pin => "-",
+            Self::Eq => "==",
+            Self::Ge => ">=",
+            Self::Gt => ">",
+            Self::Le => "<=",
+            Self::Lt => "<",
+        }
+    }
+}
+
+/// A version constraint, consisting of an operator and a version
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionSpec {
+    pub op: VersionOp,
+    pub version: String,
+}
+
+impl VersionSpec {
+    /// New up a VersionSpec
+    ///
+    /// # Arguments
+    /// * `op` - The comparison operator
+    /// * `version` - The version, as a &str or String
+    ///
+    /// # Returns
+    /// * Ok wrapping the VersionSpec, or InvalidVersion
+    pub fn new<V>(op: VersionOp, version: V) -> Result<Self, WithEntryError>
+    where
+        V: Into<String>,
+    {
+        let version = version.into();
+        if version.is_empty()
+            || version
+                .chars()
+                .any(|c| c.is_whitespace() || "<>=".contains(c))
+        {
+            return Err(WithEntryError::InvalidVersion(version));
+        }
+        Ok(Self { op, version })
+    }
+}
+
+impl fmt::Display for VersionSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op.as_str(), self.version)
+    }
+}
+
+/// A with package name along with an optional version constraint
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WithEntry {
+    pub name: String,
+    pub version_spec: Option<VersionSpec>,
+    /// Whether the with is in effect, as opposed to temporarily disabled
+    pub enabled: bool,
+}
+
+impl WithEntry {
+    /// New up a WithEntry without a version constraint
+    ///
+    /// # Arguments
+    /// * `name` - The package name
+    ///
+    /// # Returns
+    /// * Ok wrapping the WithEntry, or InvalidName
+    pub fn new<N>(name: N) -> Result<Self, WithEntryError>
+    where
+        N: Into<String>,
+    {
+        let name = name.into();
+        Self::validate_name(&name)?;
+        Ok(Self {
+            name,
+            version_spec: None,
+            enabled: true,
+        })
+    }
+
+    /// New up a WithEntry with a version constraint
+    ///
+    /// # Arguments
+    /// * `name` - The package name
+    /// * `version_spec` - The version constraint
+    ///
+    /// # Returns
+    /// * Ok wrapping the WithEntry, or InvalidName
+    pub fn with_version<N>(name: N, version_spec: VersionSpec) -> Result<Self, WithEntryError>
+    where
+        N: Into<String>,
+    {
+        let mut entry = Self::new(name)?;
+        entry.version_spec = Some(version_spec);
+        Ok(entry)
+    }
+
+    /// Parse a WithEntry from a string such as `houdini`, `houdini-18.0.348`
+    /// or `maya>=2020`, any of which may be disabled by the DISABLED_PREFIX
+    /// (eg `#~maya>=2020`)
+    ///
+    /// # Arguments
+    /// * `input` - The string to parse
+    ///
+    /// # Returns
+    /// * Ok wrapping the WithEntry, or a WithEntryError
+    pub fn parse(input: &str) -> Result<Self, WithEntryError> {
+        let input = input.trim();
+        if let Some(rest) = input.strip_prefix(DISABLED_PREFIX) {
+            let mut entry = Self::parse(rest)?;
+            entry.enabled = false;
+            return Ok(entry);
+        }
+        if input.is_empty() {
+            return Err(WithEntryError::Empty);
+        }
+        if let Some(idx) = input.find(['<', '>', '=']) {
+            let (name, rest) = input.split_at(idx);
+            let (op, version) = VersionOp::OPERATORS
+                .iter()
+                .find(|(text, _)| rest.starts_with(text))
+                .map(|(text, op)| (*op, &rest[text.len()..]))
+                .ok_or_else(|| WithEntryError::InvalidVersion(rest.to_string()))?;
+            return Self::with_version(name, VersionSpec::new(op, version)?);
+        }
+        // a dash followed by a digit introduces a pinned version. Otherwise
+        // the dash is part of the name
+        let pin = input
+            .char_indices()
+            .filter(|(_, c)| *c == '-')
+            .map(|(idx, _)| idx)
+            .find(|idx| {
+                input[idx + 1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_digit())
+            });
+        match pin {
+            Some(idx) => Self::with_version(
+                &input[..idx],
+                VersionSpec::new(VersionOp::Pin, &input[idx + 1..])?,
+            ),
+            None => Self::new(input),
+        }
+    }
+
+    /// Retrieve the with as text, without the DISABLED_PREFIX, whether or
+    /// not it is enabled
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * String formatted like `maya>=2020`
+    pub fn item_text(&self) -> String {
+        match &self.version_spec {
+            Some(spec) => format!("{}{}", self.name, spec),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Retrieve the version constraint as a string, if there is one
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(String) formatted like `>=2020`, or None
+    pub fn version_text(&self) -> Option<String> {
+        self.version_spec.as_ref().map(|spec| match spec.op {
+            VersionOp::Pin => spec.version.clone(),
+            _ => spec.to_string(),
+        })
+    }
+
+    // a valid package name is non empty, does not start with a comment, and
+    // contains neither whitespace nor any of the comparison characters
+    fn validate_name(name: &str) -> Result<(), WithEntryError> {
+        if name.is_empty()
+            || name.starts_with('#')
+            || name.chars().any(|c| c.is_whitespace() || "<>=".contains(c))
+        {
+            return Err(WithEntryError::InvalidName(name.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for WithEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.enabled {
+            write!(f, "{}", DISABLED_PREFIX)?;
+        }
+        write!(f, "{}", self.item_text())
+    }
+}
+
+impl FromStr for WithEntry {
+    type Err = WithEntryError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+/// Given the text typed by the user, suggest versioned entries once a
+/// package name has been chosen and a version has been started, as in
+/// `houdini-` or `maya>=`.
+///
+/// # Arguments
+/// * `text` - The text entered by the user
+/// * `versions` - Map of package name to its available versions
+///
+/// # Returns
+/// * Some(Vector of suggestions) if the text names a known package followed
+///   by a version, or None
+pub fn version_completions(
+    text: &str,
+    versions: &HashMap<String, Vec<String>>,
+) -> Option<Vec<String>> {
+    let text = text.trim();
+    let idx = text.find(['<', '>', '=']).or_else(|| {
+        text.rfind('-')
+            .filter(|idx| versions.contains_key(&text[..*idx]))
+    })?;
+    let name = &text[..idx];
+    let package_versions = versions.get(name)?;
+    let op = VersionOp::OPERATORS
+        .iter()
+        .find(|(op_text, _)| text[idx..].starts_with(op_text))
+        .map_or(VersionOp::Pin.as_str(), |(op_text, _)| *op_text);
+    Some(
+        package_versions
+            .iter()
+            .map(|version| format!("{}{}{}", name, op, version))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(input: &str) -> WithEntry {
+        WithEntry::parse(input).expect("valid with")
+    }
+
+    fn versions() -> HashMap<String, Vec<String>> {
+        let mut versions = HashMap::new();
+        versions.insert(
+            "houdini".to_string(),
+            vec!["18.0.348".to_string(), "18.5.532".to_string()],
+        );
+        versions.insert("maya".to_string(), vec!["2020".to_string()]);
+        versions.insert("usd-core".to_string(), vec!["21.05".to_string()]);
+        versions
+    }
+
+    #[test]
+    fn a_dash_before_a_digit_pins_the_version() {
+        let entry = parsed("houdini-18.0.348");
+        assert_eq!(entry.name, "houdini");
+        assert_eq!(
+            entry.version_spec,
+            Some(VersionSpec::new(VersionOp::Pin, "18.0.348").unwrap())
+        );
+        assert_eq!(entry.version_text(), Some("18.0.348".to_string()));
+        assert_eq!(entry.to_string(), "houdini-18.0.348");
+    }
+
+    #[test]
+    fn operators_constrain_the_version() {
+        for (input, op) in [
+            ("maya==2020", VersionOp::Eq),
+            ("maya>=2020", VersionOp::Ge),
+            ("maya<=2020", VersionOp::Le),
+            ("maya>2020", VersionOp::Gt),
+            ("maya<2020", VersionOp::Lt),
+        ] {
+            let entry = parsed(input);
+            assert_eq!(entry.name, "maya");
+            assert_eq!(entry.version_spec.as_ref().map(|spec| spec.op), Some(op));
+            assert_eq!(entry.to_string(), input);
+        }
+        assert_eq!(
+            parsed("maya>=2020").version_text(),
+            Some(">=2020".to_string())
+        );
+    }
+
+    #[test]
+    fn dashes_not_followed_by_a_digit_belong_to_the_name() {
+        let entry = parsed("usd-core");
+        assert_eq!(entry.name, "usd-core");
+        assert_eq!(entry.version_spec, None);
+        let entry = parsed("usd-core-21.05");
+        assert_eq!(entry.name, "usd-core");
+        assert_eq!(entry.version_text(), Some("21.05".to_string()));
+        assert_eq!(parsed("usd-core>=21").name, "usd-core");
+    }
+
+    #[test]
+    fn the_disabled_prefix_disables_the_with() {
+        let entry = parsed("#~maya>=2020");
+        assert!(!entry.enabled);
+        assert_eq!(entry.name, "maya");
+        assert_eq!(entry.item_text(), "maya>=2020");
+        assert_eq!(entry.to_string(), "#~maya>=2020");
+        assert!(parsed(" houdini ").enabled);
+        assert_eq!("#~nuke".parse::<WithEntry>().map(|e| e.enabled), Ok(false));
+    }
+
+    #[test]
+    fn invalid_withs_are_rejected() {
+        assert_eq!(WithEntry::parse("  "), Err(WithEntryError::Empty));
+        assert_eq!(WithEntry::parse("#~"), Err(WithEntryError::Empty));
+        assert_eq!(
+            WithEntry::parse("maya>="),
+            Err(WithEntryError::InvalidVersion(String::new()))
+        );
+        assert_eq!(
+            WithEntry::parse("maya=>2020"),
+            Err(WithEntryError::InvalidVersion("=>2020".to_string()))
+        );
+        assert_eq!(
+            WithEntry::parse("maya>=20 20"),
+            Err(WithEntryError::InvalidVersion("20 20".to_string()))
+        );
+        assert_eq!(
+            WithEntry::parse(">=2020"),
+            Err(WithEntryError::InvalidName(String::new()))
+        );
+        assert_eq!(
+            WithEntry::parse("#maya"),
+            Err(WithEntryError::InvalidName("#maya".to_string()))
+        );
+        assert!(WithEntry::new("two words").is_err());
+    }
+
+    #[test]
+    fn versions_are_completed_once_a_version_is_started() {
+        let versions = versions();
+        assert_eq!(
+            version_completions("houdini-", &versions),
+            Some(vec![
+                "houdini-18.0.348".to_string(),
+                "houdini-18.5.532".to_string()
+            ])
+        );
+        assert_eq!(
+            version_completions("maya>=", &versions),
+            Some(vec!["maya>=2020".to_string()])
+        );
+        assert_eq!(
+            version_completions("maya<2", &versions),
+            Some(vec!["maya<2020".to_string()])
+        );
+        assert_eq!(
+            version_completions("usd-core-", &versions),
+            Some(vec!["usd-core-21.05".to_string()])
+        );
+    }
+
+    #[test]
+    fn versions_are_not_completed_without_a_known_package() {
+        let versions = versions();
+        assert_eq!(version_completions("houdini", &versions), None);
+        assert_eq!(version_completions("nuke-", &versions), None);
+        assert_eq!(version_completions("nuke>=", &versions), None);
+        // the dash of a dashed name does not start a version
+        assert_eq!(version_completions("usd-", &versions), None);
+    }
+}
@@ -1,127 +1,158 @@
 use super::utility::qs;
-use crate::inner_withs_list::InnerWithsList;
+use crate::bindings::{Action, BindingError, KeyBindings};
+use crate::candidates::CandidateProvider;
+use crate::clipboard::{split_entries, PasteReport};
+use crate::config::ListMode;
+pub use crate::config::{ItemListConfig, WithsListConfig};
+use crate::dependency::DependencyResolver;
+use crate::diagnostics::{ItemDiagnostic, Validator};
+use crate::dnd;
+use crate::filter::FilterMatch;
+use crate::handle::{ItemHandle, StaleHandle};
+use crate::inner_withs_list::InnerItemList;
+use crate::list_item::ListItem;
+use crate::persist::PersistError;
 pub use crate::traits::*;
-use log;
-use qt_core::{Key, QModelIndex, QString, Slot};
-use qt_gui::{q_key_sequence::StandardKey, QKeySequence, QStandardItem, QStandardItemModel};
-use qt_widgets::{
-    cpp_core::CastInto, cpp_core::MutPtr, cpp_core::Ref as QRef, QListView, QPushButton, QShortcut,
-    QWidget,
-};
+use crate::with_entry::WithEntry;
+use crate::withs_model::ItemModelError;
+use qt_core::{QPoint, QString, Slot, SlotOfQModelIndexIntInt, SlotOfQPoint, SlotOfQString};
+use qt_gui::{QKeySequence, QStandardItem, SlotOfQStandardItem};
+use qt_widgets::{cpp_core::CastInto, cpp_core::MutPtr, cpp_core::Ref as QRef, QShortcut, QWidget};
 pub use rustqt_utils::{as_mut_ref, as_ref, enclose, enclose_all};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::Arc;
 
-/// Struct which holds configuration for the WithsList
-#[derive(Debug)]
-pub struct WithsListConfig {
-    /// The find shortcut as a string (eg Ctrl+f)
-    find_shortcut: String,
-    /// The add shortcut as a string
-    add_shortcut: String,
-}
-
-impl Default for WithsListConfig {
-    fn default() -> Self {
-        Self {
-            find_shortcut: "Ctrl+f".to_string(),
-            add_shortcut: "Ctrl+a".to_string(),
-        }
-    }
-}
-/// The WithsList provides a listview with a toolbar allowing you
+/// The ItemList provides a listview with a toolbar allowing you
 /// to switch between adding and finding members.
 /// It stores the main components that are interesting to
 /// its clients, including the toolbar, the model, the view,
-/// the actual items backing data, and various slots
-pub struct WithsList<'l> {
-    inner: Rc<InnerWithsList>,
-    enter_shortcut: MutPtr<QShortcut>,
-    delete_shortcut: MutPtr<QShortcut>,
-    cut_shortcut: MutPtr<QShortcut>,
-    find_shortcut: MutPtr<QShortcut>,
-    add_shortcut: MutPtr<QShortcut>,
+/// the actual items backing data, and various slots. Its items are
+/// parsed, identified and rendered through their ListItem implementation.
+pub struct ItemList<'l, T: ListItem> {
+    inner: Rc<InnerItemList<T>>,
+    bindings: RefCell<KeyBindings>,
+    shortcuts: RefCell<Vec<(Action, MutPtr<QShortcut>)>>,
     rm: Slot<'l>,
+    cut: Slot<'l>,
+    copy: Slot<'l>,
     find_mode: Slot<'l>,
+    filter_mode: Slot<'l>,
     add_mode: Slot<'l>,
     enter_sc: Slot<'l>,
     find_shortcut_slot: Slot<'l>,
+    filter_shortcut_slot: Slot<'l>,
     add_shortcut_slot: Slot<'l>,
+    rows_removed: SlotOfQModelIndexIntInt<'l>,
+    rows_inserted: SlotOfQModelIndexIntInt<'l>,
+    sync_view: Slot<'l>,
+    context_menu: SlotOfQPoint<'l>,
+    cb_text_changed: SlotOfQString<'l>,
+    completions_reset: Slot<'l>,
+    load_poll: Slot<'l>,
+    save: Slot<'l>,
+    revert: Slot<'l>,
+    undo: Slot<'l>,
+    redo: Slot<'l>,
+    move_up: Slot<'l>,
+    move_down: Slot<'l>,
+    move_top: Slot<'l>,
+    move_bottom: Slot<'l>,
+    toggle_enabled: Slot<'l>,
+    item_changed: SlotOfQStandardItem<'l>,
+    paste: Slot<'l>,
 }
 
-impl<'l> WithsList<'l> {
-    /// New up an WithsList given a parent
+impl<'l, T: ListItem> ItemList<'l, T> {
+    /// New up an ItemList given a parent.
+    ///
+    /// The key bindings are not rejected if they collide: the collision is
+    /// logged as an error, and the shortcuts are installed regardless, so
+    /// that qt finds them ambiguous and triggers neither action. Use
+    /// `try_new` to reject a config whose key bindings collide.
     ///
     /// # Arguments
     /// * `parent` - MutPtr to the parent QWidget
-    /// * `config` - Instance of WithsListConfig. (which implements default)
+    /// * `config` - Instance of ItemListConfig. (which implements default)
     ///
     /// # Returns
-    /// * An WithsList instance
-    pub fn new(parent: impl CastInto<MutPtr<QWidget>>, config: WithsListConfig) -> WithsList<'l> {
+    /// * An ItemList instance
+    pub fn new(parent: impl CastInto<MutPtr<QWidget>>, config: ItemListConfig) -> ItemList<'l, T> {
+        if let Err(e) = config.bindings().validate() {
+            log::error!("{}", e);
+        }
         unsafe {
-            let inner = Rc::new(InnerWithsList::new(parent.cast_into()));
-
-            // shortcuts
-            let enter_key_seq = QKeySequence::from_int(Key::KeyReturn.to_int());
-            let enter_shortcut = QShortcut::new_2a(enter_key_seq.as_ref(), inner.main());
-
-            let key_seq = QKeySequence::from_int(Key::KeyBackspace.to_int());
-            let delete_shortcut = QShortcut::new_2a(key_seq.as_ref(), inner.main());
-
-            let cut_key_seq = QKeySequence::from_standard_key(StandardKey::Cut);
-            let cut_shortcut = QShortcut::new_2a(cut_key_seq.as_ref(), inner.main());
-
-            let key_seq = QKeySequence::from_q_string(&qs(&config.find_shortcut));
-            let find_shortcut = QShortcut::new_2a(key_seq.as_ref(), inner.main());
-
-            let key_seq = QKeySequence::from_q_string(&qs(&config.add_shortcut));
-            let add_shortcut = QShortcut::new_2a(key_seq.as_ref(), inner.main());
+            let inner = Rc::new(InnerItemList::new(parent.cast_into()));
 
             // Slots
-            let inner_view = inner.view();
-            let rm_slot = Slot::new(enclose_all! { () (mut inner_view) move || {
-                let selected = inner_view.selection_model().selected_indexes();
-                if selected.length() == 0 {
-                    return;
-                }
-                // we need to sort the indexes first. Otherwise, depending upon selection order, we
-                // may not
-                let mut indexes = (0..selected.size()).into_iter().map(|x| selected.at(x).row()).collect::<Vec<_>>();
-                indexes.sort();
-                indexes.iter().rev().for_each(|c| {inner_view.model().remove_row_1a(*c); });
+            let rm_slot = Slot::new(enclose! { (inner) move || {
+                inner.remove_selected();
+            }});
+            let cut = Slot::new(enclose! { (inner) move || {
+                inner.cut_selected();
+            }});
+            let copy = Slot::new(enclose! { (inner) move || {
+                inner.copy_selected();
             }});
             // store off some references so that we can move them into teh closure
             let mut cbox_ptr = inner.add_combobox();
             let mut listview_ptr = inner.view();
-            let model_ptr = inner.model();
 
             let enter_sc = Slot::new(
                 // changed from enclose_mut to enclose since I have to make copies of the
                 // variables (as i do above) anyway. No reason to create additional copies of the pointers
                 enclose! { (inner) move || {
                     let text = cbox_ptr.current_text();
+                    // the filter is applied as the user types
+                    if inner.is_filter_active() {
+                        return;
+                    }
                     if inner.is_find_active() {
-                        if inner.scroll_to_item(text.as_ref(), true) {
+                        // fall back on the best fuzzy match for the text
+                        let found = inner.scroll_to_item(text.as_ref(), true)
+                            || inner
+                                .best_completion()
+                                .is_some_and(|best| inner.scroll_to_item(qs(best).as_ref(), true));
+                        if found {
                             cbox_ptr.clear_edit_text();
                         }
                         return;
                     }
                     // bail if text is ""
                     if QString::compare_2_q_string(&text, &qs("")) == 0 {return;}
-                    // validate that text is in the list
-                    let mut found = false;
-                    for cnt in 0..cbox_ptr.count() {
-                        let item = cbox_ptr.item_text(cnt);
-                        if QString::compare_2_q_string(&text,&item) == 0 {
-                            found = true;
-                            break;
+                    // several entries are added as a paste
+                    if split_entries(text.to_std_string().as_str()).len() > 1 {
+                        let report = inner.paste_text(text.to_std_string().as_str());
+                        if !report.added.is_empty() {
+                            cbox_ptr.clear_edit_text();
                         }
+                        inner.report_paste(&report);
+                        return;
                     }
-                    if !found {
+                    let entry = match T::parse_item(text.to_std_string().as_str()) {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            log::info!("user's entry not valid: {}", e);
+                            return;
+                        }
+                    };
+                    // validate that the item's key is one of the candidates
+                    if !inner.is_candidate(entry.key()) {
+                        // complete the text to the best fuzzy match, which the
+                        // user may then confirm
+                        if let Some(best) = inner.best_completion() {
+                            cbox_ptr.set_edit_text(&qs(best));
+                            return;
+                        }
                         log::info!("user's entry not valid");
                         return;
                     }
-                    if model_ptr.find_items_1a(&text).length() > 0 {
+                    // an item the model would reject is already in the list, so
+                    // we show it, while distinct items of a key allowed
+                    // to appear more than once are added below
+                    if inner.is_duplicate(text.to_std_string().as_str()) {
                         if inner.scroll_to_item(text.as_ref(),  true) {
 
                             cbox_ptr.clear_edit_text();
@@ -129,43 +160,173 @@ impl<'l> WithsList<'l> {
                         return;
                     }
 
-                    inner.add_item_to(text.to_std_string().as_str());
+                    if let Err(e) = inner.add_item_to(text.to_std_string().as_str()) {
+                        log::info!("unable to add item: {}", e);
+                        return;
+                    }
                     cbox_ptr.clear_edit_text();
                     listview_ptr.scroll_to_bottom();
+                    inner.offer_dependencies(text.to_std_string().as_str());
 
                 }},
             );
             let find_shortcut_slot = Slot::new(enclose! { (inner) move || {
                 inner.set_find_mode();
             }});
+            let filter_shortcut_slot = Slot::new(enclose! { (inner) move || {
+                inner.set_filter_mode();
+            }});
             let add_shortcut_slot = Slot::new(enclose! { (inner) move || {
                 inner.set_add_mode();
             }});
+            // the view moves rows by inserting copies and then removing the
+            // originals, and drops between lists insert and remove rows in
+            // separate models, so we sync the model once the drag is over
+            let rows_removed = SlotOfQModelIndexIntInt::new(enclose! { (inner) move |_, _, _| {
+                inner.schedule_sync_from_view();
+            }});
+            let rows_inserted = SlotOfQModelIndexIntInt::new(enclose! { (inner) move |_, _, _| {
+                inner.schedule_sync_from_view();
+            }});
+            let sync_view = Slot::new(enclose! { (inner) move || {
+                inner.sync_from_view();
+            }});
+            let context_menu = SlotOfQPoint::new(enclose! { (inner) move |pos: QRef<QPoint>| {
+                inner.show_context_menu(pos);
+            }});
+            let cb_text_changed =
+                SlotOfQString::new(enclose! { (inner) move |text: QRef<QString>| {
+                    let text = text.to_std_string();
+                    // the combobox receives pasted text itself, so we pick out
+                    // pastes holding several lines, which may not be typed
+                    if inner.is_add_active() && text.contains('\n') {
+                        let mut cbox = inner.add_combobox();
+                        let report = inner.paste_text(text.as_str());
+                        cbox.clear_edit_text();
+                        inner.report_paste(&report);
+                        return;
+                    }
+                    // as are the files dropped from a file browser
+                    if inner.is_add_active()
+                        && inner.is_drag_drop_enabled()
+                        && dnd::is_uri_list(text.as_str())
+                    {
+                        let mut cbox = inner.add_combobox();
+                        let report = inner.drop_text(text.as_str());
+                        cbox.clear_edit_text();
+                        inner.report_paste(&report);
+                        return;
+                    }
+                    inner.update_completions(text.as_str());
+                    if inner.is_filter_active() {
+                        inner.set_filter_text(text.as_str());
+                    }
+                }});
+            let completions_reset = Slot::new(enclose! { (inner) move || {
+                inner.highlight_completions();
+            }});
+            let load_poll = Slot::new(enclose! { (inner) move || {
+                inner.poll_cb_items_load();
+            }});
+            let save = Slot::new(enclose! { (inner) move || {
+                inner.save();
+            }});
+            let revert = Slot::new(enclose! { (inner) move || {
+                inner.revert();
+            }});
+            let undo = Slot::new(enclose! { (inner) move || {
+                inner.undo();
+            }});
+            let redo = Slot::new(enclose! { (inner) move || {
+                inner.redo();
+            }});
+            let move_up = Slot::new(enclose! { (inner) move || {
+                inner.move_selected_by(-1);
+            }});
+            let move_down = Slot::new(enclose! { (inner) move || {
+                inner.move_selected_by(1);
+            }});
+            let move_top = Slot::new(enclose! { (inner) move || {
+                inner.move_selected_to(0);
+            }});
+            let move_bottom = Slot::new(enclose! { (inner) move || {
+                inner.move_selected_to(usize::MAX);
+            }});
+            let toggle_enabled = Slot::new(enclose! { (inner) move || {
+                inner.toggle_selected_enabled();
+            }});
+            let item_changed =
+                SlotOfQStandardItem::new(enclose! { (inner) move |item: MutPtr<QStandardItem>| {
+                    inner.sync_enabled_from_view(item);
+                }});
+            let paste = Slot::new(enclose! { (inner) move || {
+                let report = inner.paste();
+                inner.report_paste(&report);
+            }});
             let cblabel = inner.add_label();
-            let f = Self {
-                inner,
-                enter_shortcut: enter_shortcut.into_ptr(),
-                delete_shortcut: delete_shortcut.into_ptr(),
-                cut_shortcut: cut_shortcut.into_ptr(),
-                find_shortcut: find_shortcut.into_ptr(),
-                add_shortcut: add_shortcut.into_ptr(),
-                rm: rm_slot,
-
-                find_mode: Slot::new(as_mut_ref! { (cblabel) enclose! { (cbox_ptr) move || {
+            let find_label = config.find_label().to_string();
+            let filter_label = config.filter_label().to_string();
+            let add_label = config.add_label().to_string();
+            // leaving the filter mode shows all of the items again
+            let find_mode = Slot::new(
+                as_mut_ref! { (cblabel) enclose! { (inner, cbox_ptr) move || {
                     let mut cbox_ptr = cbox_ptr;
                     cbox_ptr.set_enabled(true);
-                    if let Some(mut cblabel) = cblabel {cblabel.set_text(&qs("Find Item"))};
-                }}}),
-
-                add_mode: Slot::new(as_mut_ref! {(cblabel) enclose! { ( cbox_ptr) move || {
+                    inner.clear_filter();
+                    inner.update_completions(cbox_ptr.current_text().to_std_string().as_str());
+                    if let Some(mut cblabel) = cblabel {cblabel.set_text(&qs(&find_label))};
+                }}},
+            );
+            let filter_mode = Slot::new(
+                as_mut_ref! { (cblabel) enclose! { (inner, cbox_ptr) move || {
                     let mut cbox_ptr = cbox_ptr;
                     cbox_ptr.set_enabled(true);
-                    if let Some(mut cblabel) = cblabel {cblabel.set_text(&qs("Add Item"))};
-                }}}),
-
+                    inner.set_filter_text(cbox_ptr.current_text().to_std_string().as_str());
+                    inner.update_completions(cbox_ptr.current_text().to_std_string().as_str());
+                    if let Some(mut cblabel) = cblabel {cblabel.set_text(&qs(&filter_label))};
+                }}},
+            );
+            let add_mode = Slot::new(
+                as_mut_ref! { (cblabel) enclose! { (inner, cbox_ptr) move || {
+                    let mut cbox_ptr = cbox_ptr;
+                    cbox_ptr.set_enabled(true);
+                    inner.clear_filter();
+                    inner.update_completions(cbox_ptr.current_text().to_std_string().as_str());
+                    if let Some(mut cblabel) = cblabel {cblabel.set_text(&qs(&add_label))};
+                }}},
+            );
+            let f = Self {
+                inner,
+                bindings: RefCell::new(config.bindings().clone()),
+                shortcuts: RefCell::new(Vec::new()),
+                rm: rm_slot,
+                cut,
+                copy,
+                find_mode,
+                filter_mode,
+                add_mode,
                 enter_sc,
                 find_shortcut_slot,
+                filter_shortcut_slot,
                 add_shortcut_slot,
+                rows_removed,
+                rows_inserted,
+                sync_view,
+                context_menu,
+                cb_text_changed,
+                completions_reset,
+                load_poll,
+                save,
+                revert,
+                undo,
+                redo,
+                move_up,
+                move_down,
+                move_top,
+                move_bottom,
+                toggle_enabled,
+                item_changed,
+                paste,
             };
             // Wire up signals and slots
             f.inner()
@@ -173,40 +334,166 @@ impl<'l> WithsList<'l> {
                 .triggered()
                 .connect(&f.find_mode);
 
+            f.inner()
+                .filter_mode_action()
+                .triggered()
+                .connect(&f.filter_mode);
+
             f.inner().add_mode_action().triggered().connect(&f.add_mode);
-            f.enter_shortcut.activated().connect(&f.enter_sc);
-            f.delete_shortcut.activated().connect(&f.rm);
-            f.cut_shortcut.activated().connect(&f.rm);
-            f.find_shortcut.activated().connect(&f.find_shortcut_slot);
-            f.add_shortcut.activated().connect(&f.add_shortcut_slot);
+            for action in Action::ALL.iter() {
+                f.install_shortcuts(*action);
+            }
+            f.inner().model().rows_removed().connect(&f.rows_removed);
+            f.inner().model().rows_inserted().connect(&f.rows_inserted);
+            f.inner().model().item_changed().connect(&f.item_changed);
+            f.inner().sync_timer().timeout().connect(&f.sync_view);
+            f.inner()
+                .view()
+                .custom_context_menu_requested()
+                .connect(&f.context_menu);
+            f.inner()
+                .add_combobox()
+                .edit_text_changed()
+                .connect(&f.cb_text_changed);
+            f.inner()
+                .completion_model()
+                .model_reset()
+                .connect(&f.completions_reset);
+            f.inner().load_timer().timeout().connect(&f.load_poll);
+            f.inner().save_button().clicked().connect(&f.save);
+            f.inner().revert_button().clicked().connect(&f.revert);
+            // apply the remainder of the config
+            f.inner().add_label().set_text(&qs(config.add_label()));
+            if let Some(max) = config.max_visible_items() {
+                f.set_cb_max_visible_items(max);
+            }
+            if let Some(sheet) = config.stylesheet() {
+                f.set_stylesheet(&sheet.to_string_lossy());
+            }
+            f.set_allowed_duplicates(config.allowed_duplicates().to_vec());
+            match config.default_mode() {
+                ListMode::Add => (),
+                ListMode::Find => f.set_find_mode(),
+                ListMode::Filter => f.set_filter_mode(),
+            }
             f
         }
     }
 
-    // Retrieve an RC wrapped InnerWithsList instance. This used to be public
-    // but it really has no place as part of the public api. All use cases
-    // should be covered by explicit methods.
-    //
-    // # Arguments
-    // * None
-    //
-    // # Returns
-    // * Rc of the InnerWithsList instance
-    fn inner(&self) -> Rc<InnerWithsList> {
-        self.inner.clone()
+    /// New up an ItemList given a parent, first validating the config
+    ///
+    /// # Arguments
+    /// * `parent` - MutPtr to the parent QWidget
+    /// * `config` - Instance of ItemListConfig
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemList instance, or a BindingError if any of
+    ///   the key bindings collide
+    pub fn try_new(
+        parent: impl CastInto<MutPtr<QWidget>>,
+        config: ItemListConfig,
+    ) -> Result<ItemList<'l, T>, BindingError> {
+        config.bindings().validate()?;
+        Ok(Self::new(parent, config))
     }
 
-    /// Retrieve a mutable pointer to the component's top QWidget. That is
-    /// the widget contained within that is the parent of the other internal
-    /// widgets.
+    /// Retrieve a copy of the key bindings
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * Mutable Pointer to the main QWidget
-    pub fn main(&self) -> MutPtr<QWidget> {
-        self.inner().main()
+    /// * KeyBindings
+    pub fn key_bindings(&self) -> KeyBindings {
+        self.bindings.borrow().clone()
+    }
+
+    /// Bind the sequences to the action, replacing its existing sequences.
+    /// The bindings are left untouched if any of the sequences is invalid,
+    /// or collides with a sequence of another action.
+    ///
+    /// # Arguments
+    /// * `action` - The Action
+    /// * `sequences` - The key sequences, as &str or String (eg `Ctrl+F`)
+    ///
+    /// # Returns
+    /// * Ok, or a BindingError
+    pub fn rebind<S>(&self, action: Action, sequences: &[S]) -> Result<(), BindingError>
+    where
+        S: AsRef<str>,
+    {
+        self.bindings.borrow_mut().bind(action, sequences)?;
+        self.install_shortcuts(action);
+        Ok(())
+    }
+
+    /// Replace all of the key bindings
+    ///
+    /// # Arguments
+    /// * `bindings` - The KeyBindings
+    ///
+    /// # Returns
+    /// * Ok, or a BindingError if any of the key bindings collide, in which
+    ///   case the bindings are left untouched
+    pub fn set_key_bindings(&self, bindings: KeyBindings) -> Result<(), BindingError> {
+        bindings.validate()?;
+        self.bindings.replace(bindings);
+        for action in Action::ALL.iter() {
+            self.install_shortcuts(*action);
+        }
+        Ok(())
+    }
+
+    // Replace the action's shortcuts with ones for its current key bindings
+    fn install_shortcuts(&self, action: Action) {
+        let mut shortcuts = self.shortcuts.borrow_mut();
+        unsafe {
+            for (_, shortcut) in shortcuts.iter_mut().filter(|(a, _)| *a == action) {
+                shortcut.set_enabled(false);
+                shortcut.delete_later();
+            }
+            shortcuts.retain(|(a, _)| *a != action);
+            for sequence in self.bindings.borrow().sequences(action) {
+                let key_seq = QKeySequence::from_q_string(&qs(sequence.to_string()));
+                let shortcut = QShortcut::new_2a(key_seq.as_ref(), self.inner.main()).into_ptr();
+                shortcut.activated().connect(self.action_slot(action));
+                shortcuts.push((action, shortcut));
+            }
+        }
+    }
+
+    // The slot invoked by the action's shortcuts
+    fn action_slot(&self, action: Action) -> &Slot<'l> {
+        match action {
+            Action::Enter => &self.enter_sc,
+            Action::Delete => &self.rm,
+            Action::Cut => &self.cut,
+            Action::Copy => &self.copy,
+            Action::Paste => &self.paste,
+            Action::Undo => &self.undo,
+            Action::Redo => &self.redo,
+            Action::FindMode => &self.find_shortcut_slot,
+            Action::FilterMode => &self.filter_shortcut_slot,
+            Action::AddMode => &self.add_shortcut_slot,
+            Action::MoveUp => &self.move_up,
+            Action::MoveDown => &self.move_down,
+            Action::MoveToTop => &self.move_top,
+            Action::MoveToBottom => &self.move_bottom,
+            Action::ToggleEnabled => &self.toggle_enabled,
+        }
+    }
+
+    // Retrieve an RC wrapped InnerItemList instance. This used to be public
+    // but it really has no place as part of the public api. All use cases
+    // should be covered by explicit methods, or by the raw module.
+    //
+    // # Arguments
+    // * None
+    //
+    // # Returns
+    // * Rc of the InnerItemList instance
+    pub(crate) fn inner(&self) -> Rc<InnerItemList<T>> {
+        self.inner.clone()
     }
 
     #[allow(dead_code)]
@@ -223,101 +510,245 @@ impl<'l> WithsList<'l> {
 
     #[allow(dead_code)]
     /// Sets the contents to items, removing any pre-existing
-    /// items.
+    /// items. The items become the baseline against which `is_dirty`
+    /// is measured. The list is left untouched if any of the items
+    /// is invalid or duplicated.
     ///
     /// # Arguments
     /// * `items` - a Vector of &str or String
     ///
     /// # Returns
-    /// None
-    pub fn set_items<I>(&self, items: Vec<I>)
+    /// * Ok, or the first ItemModelError encountered
+    pub fn set_items<I>(&self, items: Vec<I>) -> Result<(), ItemModelError>
     where
         I: AsRef<str>,
     {
-        self.clear();
-        let inner = self.inner();
-        for item in items {
-            inner.add_item(item.as_ref());
-        }
+        self.inner().set_items(items)
+    }
+
+    /// Determine whether the items differ from the baseline set by
+    /// the last call to `set_items` or the last successful save
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the list has unsaved edits
+    pub fn is_dirty(&self) -> bool {
+        self.inner().is_dirty()
+    }
+
+    /// Register the handler invoked with the items when the user presses
+    /// the Save button, replacing any previously registered handler. The
+    /// handler receives all of the items, as per `all_items`, so that
+    /// disabled items are saved with the DISABLED_PREFIX. It returns
+    /// whether the save succeeded, in which case the items become the new
+    /// baseline.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking the items and returning a bool
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_save<F>(&self, callback: F)
+    where
+        F: Fn(Vec<String>) -> bool + 'static,
+    {
+        self.inner().set_save_callback(Box::new(callback));
+    }
+
+    /// Save the items by invoking the registered save handler. Saving
+    /// fails without invoking the handler if saving is blocked by errors
+    /// (see `set_block_save_on_errors`).
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the items were saved
+    pub fn save(&self) -> bool {
+        self.inner().save()
+    }
+
+    /// Restore the items from the baseline, discarding any edits
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn revert(&self) {
+        self.inner().revert();
+    }
+
+    /// Undo the last edit made to the list. Adds, removals, moves,
+    /// clears and `set_items` may all be undone.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether there was an edit to undo
+    pub fn undo(&self) -> bool {
+        self.inner().undo()
+    }
+
+    /// Redo the last undone edit
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether there was an edit to redo
+    pub fn redo(&self) -> bool {
+        self.inner().redo()
     }
 
-    /// Retrieve the model for the component
+    /// Determine whether there is an edit to undo
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * MutPtr wrapping QStandardItemModel
-    pub fn model(&self) -> MutPtr<QStandardItemModel> {
-        self.inner().model()
+    /// * bool
+    pub fn can_undo(&self) -> bool {
+        self.inner.history().can_undo()
     }
 
-    /// Retrieve the primary list view.
+    /// Determine whether there is an edit to redo
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * MutPtr to QListView
-    pub fn view(&self) -> MutPtr<QListView> {
-        self.inner().view()
+    /// * bool
+    pub fn can_redo(&self) -> bool {
+        self.inner.history().can_redo()
     }
 
-    /// Retrieve a MutPtr to the save button
+    /// Retrieve the labels of the edits which may be undone (eg "Add houdini"),
+    /// most recent first
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * MutPtr to QPushButton
-    pub fn save_button(&self) -> MutPtr<QPushButton> {
-        self.inner().save_button()
+    /// * Vector of String
+    pub fn undo_labels(&self) -> Vec<String> {
+        self.inner
+            .history()
+            .undo_labels()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Retrieve the labels of the edits which may be redone, most recent first
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of String
+    pub fn redo_labels(&self) -> Vec<String> {
+        self.inner
+            .history()
+            .redo_labels()
+            .into_iter()
+            .map(String::from)
+            .collect()
     }
+
     /// add an item to the pulldown
     ///
     /// # Arguments
     /// * The item to be added, as a &str or String
     ///
     /// # Returns
-    /// * None
-    pub fn add_item<I>(&self, item: I)
+    /// * Ok wrapping the row of the new item, or an ItemModelError if
+    ///   the item is invalid or already in the list
+    pub fn add_item<I>(&self, item: I) -> Result<usize, ItemModelError>
     where
         I: AsRef<str>,
     {
-        self.inner().add_item_to(item.as_ref()); //, &mut self.model.as_mut_ptr());
+        self.inner().add_item_to(item.as_ref())
     }
 
-    /// add an item to the pulldown
+    /// Retrieve a handle to the item with the supplied text, or else the
+    /// supplied key. The handle remains usable until the items next change.
     ///
     /// # Arguments
-    /// * The item to be found, as a &MutPtr<QString>
-    pub fn find_item<'a>(&self, item: QRef<QString>) -> Option<MutPtr<QStandardItem>> {
-        self.inner().find_item(item)
+    /// * `item` - The item, or its key (eg `houdini`)
+    ///
+    /// # Returns
+    /// * Some(ItemHandle), or None if the item is not in the list
+    pub fn find_item(&self, item: &str) -> Option<ItemHandle> {
+        self.inner().handle(item)
     }
 
-    /// scroll to the provided item in the list
+    /// Retrieve the current row of the item identified by a handle
     ///
     /// # Arguments
-    /// * `item` - A Ref wrapped QString.
-    /// * `select1 - a boolean indicating whether the item should be selected as well as centered
-    /// in the view
+    /// * `handle` - The ItemHandle, as returned by `find_item`
     ///
     /// # Returns
-    /// * None
-    pub fn scroll_to_item<'a>(&self, item: QRef<QString>, select_item: bool) {
-        self.inner().scroll_to_item(item, select_item);
+    /// * Ok wrapping the row, or StaleHandle if the items have changed
+    ///   since the handle was made
+    pub fn row_of(&self, handle: &ItemHandle) -> Result<usize, StaleHandle> {
+        self.inner().resolve(handle)
+    }
+
+    /// Scroll the view so that the item is visible. The item is looked up
+    /// as per `find_item`: an item with the supplied text is preferred,
+    /// falling back to the first item with the supplied key. There is no
+    /// prefix or fuzzy matching. Items hidden by the filter are not
+    /// scrolled to.
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    /// * `select_item` - Whether the item should be selected as well
+    ///
+    /// # Returns
+    /// * true if the item was found and is shown, false otherwise
+    pub fn scroll_to_item(&self, item: &str, select_item: bool) -> bool {
+        self.inner().scroll_to_item(qs(item).as_ref(), select_item)
+    }
+
+    /// Select the item identified by a handle, replacing the selection
+    ///
+    /// # Arguments
+    /// * `handle` - The ItemHandle, as returned by `find_item`
+    ///
+    /// # Returns
+    /// * Ok, or StaleHandle if the items have changed since the handle was
+    ///   made
+    pub fn select_item(&self, handle: &ItemHandle) -> Result<(), StaleHandle> {
+        let row = self.inner().resolve(handle)?;
+        self.inner().select_rows(row..row + 1);
+        Ok(())
     }
 
-    /// Select the provided item given a Ref wrapped QModelIndex
+    /// Select a range of rows, replacing the selection. Rows which are
+    /// hidden by the filter are skipped.
     ///
     /// # Arguments
-    /// * `item` - QModelIndex of the item we wish to select
+    /// * `rows` - The range of rows
     ///
     /// # Returns
     /// * None
-    #[allow(dead_code)]
-    pub fn select_item(&self, item: QRef<QModelIndex>) {
-        self.inner().select_item(item);
+    pub fn select_rows(&self, rows: Range<usize>) {
+        self.inner().select_rows(rows);
+    }
+
+    /// Retrieve the selected items, in the order they appear in the list
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items
+    pub fn selected_items(&self) -> Vec<String> {
+        self.inner().selected_items()
     }
 
     #[allow(dead_code)]
@@ -329,98 +760,869 @@ impl<'l> WithsList<'l> {
     /// # Returns
     /// None
     pub fn delete_sel_items(&self) {
-        unsafe {
-            let selected = self.view().selection_model().selected_indexes();
-            if selected.length() == 0 {
-                return;
-            }
-            let mut view_model = self.view().model();
-            for c in 0..selected.length() {
-                view_model.remove_row_1a(c);
-            }
-        }
+        self.remove_selected();
     }
 
-    /// Get the items as a vector of Strings.
+    /// Remove the selected items from the list, as the Backspace and
+    /// Cut shortcuts do.
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
-    /// * Vector of String
-    pub fn items(&self) -> Vec<String> {
-        self.inner().items()
+    /// * Vector of the removed items, in the order they appeared in the list
+    pub fn remove_selected(&self) -> Vec<String> {
+        self.inner().remove_selected()
     }
 
-    #[allow(dead_code)]
-    /// Set comboboc items, replacing any extant items
+    /// Remove the supplied items from the list
     ///
     /// # Arguments
-    /// * `items` - Vector of items
+    /// * `items` - slice of items, or their keys
+    ///
+    /// # Returns
+    /// * Ok wrapping the removed items in the order they appeared in the list,
+    ///   or an ItemModelError if any of the items is not in the list, in which
+    ///   case nothing is removed
+    pub fn remove_items(&self, items: &[&str]) -> Result<Vec<String>, ItemModelError> {
+        self.inner().remove_items(items)
+    }
+
+    /// Remove the items in the supplied range of rows
+    ///
+    /// # Arguments
+    /// * `rows` - Range of rows to remove
     ///
     /// # Returns
+    /// * Ok wrapping the removed items in row order, or an ItemModelError if
+    ///   the range extends past the end of the list, in which case nothing is
+    ///   removed
+    pub fn remove_rows(&self, rows: Range<usize>) -> Result<Vec<String>, ItemModelError> {
+        self.inner().remove_rows(&rows.collect::<Vec<_>>())
+    }
+
+    /// Get the enabled items as a vector of Strings, each formatted as per
+    /// its Display implementation (eg `houdini-18.0.348`)
+    ///
+    /// # Arguments
     /// * None
-    pub fn set_cb_items<'c, I>(&self, items: Vec<I>)
-    where
-        I: AsRef<str>,
-    {
-        self.inner().set_cb_items(items);
+    ///
+    /// # Returns
+    /// * Vector of String
+    pub fn items(&self) -> Vec<String> {
+        self.inner().items()
     }
 
-    #[allow(dead_code)]
-    /// Remove all items from the combobox
+    /// Get all of the items, enabled or not, as a vector of Strings.
+    /// Disabled items carry the DISABLED_PREFIX (eg `#~houdini-18.0.348`),
+    /// and may be restored as such via `set_items`.
     ///
     /// # Arguments
     /// * None
     ///
-    /// # Returns None
-    pub fn remove_cb_items(&self) {
-        self.inner().remove_cb_items();
+    /// # Returns
+    /// * Vector of String
+    pub fn all_items(&self) -> Vec<String> {
+        self.inner().all_items()
     }
 
-    /// Change the max number of items displayed in the combobox's dropdown
-    /// list
+    /// Enable or disable an item. Disabled items keep their place in the
+    /// list, but are left out of `items`.
     ///
     /// # Arguments
-    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    /// * `item` - The item, or its key
+    /// * `enabled` - Whether the item should be enabled
     ///
     /// # Returns
-    /// * None
-    pub fn set_cb_max_visible_items(&self, max: i32) {
-        self.inner().set_cb_max_visible_items(max);
+    /// * Ok, or NotFound if the item is not in the list
+    pub fn set_item_enabled(&self, item: &str, enabled: bool) -> Result<(), ItemModelError> {
+        self.inner().set_item_enabled(item, enabled)
     }
 
-    /// Given a path as a &str to a stylesheet, apply it to the components.
+    /// Determine whether an item is enabled
     ///
     /// # Arguments
-    /// * `sheet` - Path to the qss stylesheet
+    /// * `item` - The item, or its key
     ///
     /// # Returns
-    /// * None
-    pub fn set_stylesheet(&self, sheet: &str) {
-        self.inner().set_stylesheet(sheet);
+    /// * Some(bool), or None if the item is not in the list
+    pub fn is_item_enabled(&self, item: &str) -> Option<bool> {
+        self.inner().is_item_enabled(item)
     }
 
-    /// Set the component to add mode
+    /// Disable the selected items, or enable them if all of them are
+    /// disabled, as the ToggleEnabled shortcut does
     ///
     /// # Arguments
     /// * None
     ///
     /// # Returns
     /// * None
-    pub fn set_add_mode(&self) {
-        self.inner().set_add_mode();
+    pub fn toggle_selected_enabled(&self) {
+        self.inner().toggle_selected_enabled();
     }
 
-    #[allow(dead_code)]
-    /// Set the component to find mode
+    /// Lock the supplied items at the top of the list, in the supplied
+    /// order, replacing any previously locked, as for items mandated by
+    /// the show's configuration. Items absent from the list are added.
+    /// Locked items display a lock icon, and a tooltip naming the source
+    /// of the lock, and may not be removed, cut, dragged, moved or disabled.
+    /// An empty vector unlocks all of the items.
     ///
     /// # Arguments
-    /// * None
+    /// * `items` - Vector of items, as &str or String
+    /// * `source` - Describes what locked the items (eg `show config`)
+    ///
+    /// # Returns
+    /// * Ok, or the first ItemModelError encountered, in which case the
+    ///   list is left untouched
+    pub fn set_locked_items<I>(&self, items: Vec<I>, source: &str) -> Result<(), ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        self.inner().set_locked_items(items, source)
+    }
+
+    /// Retrieve the locked items, in the order they appear at the top of
+    /// the list
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub fn locked_items(&self) -> Vec<String> {
+        self.inner().locked_items()
+    }
+
+    /// Determine whether an item is locked
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * Some(bool), or None if the item is not in the list
+    pub fn is_item_locked(&self, item: &str) -> Option<bool> {
+        self.inner().is_item_locked(item)
+    }
+
+    /// Add a validator which reports diagnostics against the items. The
+    /// diagnostics are displayed as an icon and tooltip on each item, and
+    /// are recomputed whenever the items change.
+    ///
+    /// # Arguments
+    /// * `validator` - The Validator, eg ConflictValidator
+    ///
+    /// # Returns
+    /// * None
+    pub fn add_validator<V>(&self, validator: V)
+    where
+        V: Validator<T> + 'static,
+    {
+        self.inner().add_validator(Box::new(validator));
+    }
+
+    /// Remove all of the validators
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_validators(&self) {
+        self.inner().clear_validators();
+    }
+
+    /// Retrieve the diagnostics reported against the items by the validators
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of ItemDiagnostics, ordered by row
+    pub fn diagnostics(&self) -> Vec<ItemDiagnostic> {
+        self.inner().diagnostics()
+    }
+
+    /// Determine whether any of the items has an error diagnostic
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn has_errors(&self) -> bool {
+        self.inner().has_errors()
+    }
+
+    /// Set whether saving is blocked while any of the items has an error
+    /// diagnostic. Defaults to false.
+    ///
+    /// # Arguments
+    /// * `block` - Whether to block saving
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_block_save_on_errors(&self, block: bool) {
+        self.inner().set_block_save_on_errors(block);
+    }
+
+    /// Add the items held by the text on the clipboard. The items may be
+    /// separated by newlines, commas or whitespace.
+    /// Each is validated as it is when entered in the combobox. The valid
+    /// items are inserted at the first selected row, or appended if nothing
+    /// is selected. This is bound to the paste shortcut (eg Ctrl+V), which
+    /// also informs the user of any rejected items.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * PasteReport describing the items which were added and rejected
+    pub fn paste(&self) -> PasteReport {
+        self.inner().paste()
+    }
+
+    /// Add the items held by the supplied text, as per `paste`
+    ///
+    /// # Arguments
+    /// * `text` - The text holding the items
+    ///
+    /// # Returns
+    /// * PasteReport describing the items which were added and rejected
+    pub fn paste_text(&self, text: &str) -> PasteReport {
+        self.inner().paste_text(text)
+    }
+
+    /// Place the selected items on the clipboard, in row order, as plain
+    /// text with an item per line, as a json array, and as a document
+    /// preserving the order and details of the items, which another
+    /// ItemList prefers when pasting. This is bound to the copy shortcut
+    /// (eg Ctrl+C).
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The copied items, in row order
+    pub fn copy_selected(&self) -> Vec<String> {
+        self.inner().copy_selected()
+    }
+
+    /// Place the selected items on the clipboard, as per `copy_selected`,
+    /// and remove them from the list. This is bound to the cut shortcut
+    /// (eg Ctrl+X).
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The removed items, in row order
+    pub fn cut_selected(&self) -> Vec<String> {
+        self.inner().cut_selected()
+    }
+
+    /// Append an action to the list's context menu, following the built in
+    /// actions. When the user triggers the action, the callback is invoked
+    /// with the selected items, in row order.
+    ///
+    /// # Arguments
+    /// * `label` - The text of the action
+    /// * `callback` - Closure taking a slice of the selected items
+    ///
+    /// # Returns
+    /// * None
+    pub fn add_context_action<F>(&self, label: &str, callback: F)
+    where
+        F: Fn(&[String]) + 'static,
+    {
+        self.inner().add_context_action(label, Box::new(callback));
+    }
+
+    /// Remove the actions appended to the list's context menu by
+    /// `add_context_action`. The built in actions remain.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_context_actions(&self) {
+        self.inner().clear_context_actions();
+    }
+
+    /// Switch to add mode, presenting the item's key, and any versions of
+    /// it, among the combobox's completions
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * None
+    pub fn reveal_in_candidates(&self, item: &str) {
+        self.inner().reveal_in_candidates(item);
+    }
+
+    /// Move the items at the supplied rows, as a contiguous block in their
+    /// current order, so that the first of them ends up at row `to`. The
+    /// moved items are selected, and the scroll position is preserved. The
+    /// move is recorded as a single edit.
+    ///
+    /// # Arguments
+    /// * `indices` - The current rows of the items, in any order
+    /// * `to` - The row the first with should occupy after the move. Rows
+    ///   past the end move the items to the bottom
+    ///
+    /// # Returns
+    /// * Ok wrapping the range of rows occupied by the items after the move,
+    ///   or OutOfRange if any of the indices is out of bounds
+    pub fn move_items(&self, indices: &[usize], to: usize) -> Result<Range<usize>, ItemModelError> {
+        self.inner().move_rows(indices, to)
+    }
+
+    /// Move the selected items up a row, as bound to the move up shortcut
+    /// (eg Alt+Up)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn move_selected_up(&self) {
+        self.inner().move_selected_by(-1);
+    }
+
+    /// Move the selected items down a row, as bound to the move down
+    /// shortcut (eg Alt+Down)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn move_selected_down(&self) {
+        self.inner().move_selected_by(1);
+    }
+
+    /// Move the selected items to the top of the list, as bound to the move
+    /// to top shortcut (eg Alt+Home)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn move_selected_to_top(&self) {
+        self.inner().move_selected_to(0);
+    }
+
+    /// Move the selected items to the bottom of the list, as bound to the
+    /// move to bottom shortcut (eg Alt+End)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn move_selected_to_bottom(&self) {
+        self.inner().move_selected_to(usize::MAX);
+    }
+
+    /// Enable or disable drag and drop between ItemLists. When enabled,
+    /// items may be dragged to another ItemList with drag and drop
+    /// enabled, preserving their order and details. The items are moved,
+    /// unless the copy modifier (eg Ctrl) is held. Dropped items are
+    /// validated as per `paste`, and those rejected are reported. Files
+    /// dropped onto the combobox from a file browser are added, their
+    /// paths mapped to items by the path mapper. Defaults to
+    /// false, in which case items may only be moved within the list.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to enable drag and drop between lists
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_drag_drop_enabled(&self, enabled: bool) {
+        self.inner().set_drag_drop_enabled(enabled);
+    }
+
+    /// Determine whether drag and drop between ItemLists is enabled
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_drag_drop_enabled(&self) -> bool {
+        self.inner().is_drag_drop_enabled()
+    }
+
+    /// Set the mapper which turns the paths of dropped files into items. By
+    /// default, the file name, less any extension, is used.
+    ///
+    /// # Arguments
+    /// * `mapper` - Closure taking the path of a dropped file, and returning
+    ///   Some(item), or None if the path does not map to an item
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_path_mapper<F>(&self, mapper: F)
+    where
+        F: Fn(&Path) -> Option<String> + 'static,
+    {
+        self.inner().set_path_mapper(Box::new(mapper));
+    }
+
+    /// Add the items held by dropped text, as supplied by a drop onto
+    /// another widget. A uri list has its paths mapped to items by
+    /// the path mapper, while other text is added as per `paste_text`.
+    ///
+    /// # Arguments
+    /// * `text` - The dropped text
+    ///
+    /// # Returns
+    /// * PasteReport describing the items which were added and rejected
+    pub fn drop_text(&self, text: &str) -> PasteReport {
+        self.inner().drop_text(text)
+    }
+
+    /// Get the items as a vector of ListItems.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of the items
+    pub fn entries(&self) -> Vec<T> {
+        self.inner().entries()
+    }
+
+    /// Register a callback invoked with the list's items after any change
+    /// to its contents, whether made by the user or programmatically. The
+    /// items are those of `all_items`, disabled items carrying the
+    /// DISABLED_PREFIX.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking a slice of the items
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_items_changed<F>(&self, callback: F)
+    where
+        F: Fn(&[String]) + 'static,
+    {
+        self.inner()
+            .callbacks_mut()
+            .on_items_changed(Box::new(callback));
+    }
+
+    /// Register a callback invoked after an item has been added to the list
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking the name of the item and its row
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_item_added<F>(&self, callback: F)
+    where
+        F: Fn(&str, usize) + 'static,
+    {
+        self.inner()
+            .callbacks_mut()
+            .on_item_added(Box::new(callback));
+    }
+
+    /// Register a callback invoked after an item has been removed from the list
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking the name of the item and the row it occupied
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_item_removed<F>(&self, callback: F)
+    where
+        F: Fn(&str, usize) + 'static,
+    {
+        self.inner()
+            .callbacks_mut()
+            .on_item_removed(Box::new(callback));
+    }
+
+    /// Register a callback invoked after the items have been reordered,
+    /// as when the user drags items within the view
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking a slice of all of the items, as per
+    ///   `all_items`, in their new order
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_reordered<F>(&self, callback: F)
+    where
+        F: Fn(&[String]) + 'static,
+    {
+        self.inner()
+            .callbacks_mut()
+            .on_reordered(Box::new(callback));
+    }
+
+    /// Register a callback invoked after an item has been enabled or
+    /// disabled
+    ///
+    /// # Arguments
+    /// * `callback` - Closure taking the item, carrying the DISABLED_PREFIX
+    ///   if it is now disabled, its row, and whether it is now enabled
+    ///
+    /// # Returns
+    /// * None
+    pub fn on_item_toggled<F>(&self, callback: F)
+    where
+        F: Fn(&str, usize, bool) + 'static,
+    {
+        self.inner()
+            .callbacks_mut()
+            .on_item_toggled(Box::new(callback));
+    }
+
+    #[allow(dead_code)]
+    /// Set comboboc items, replacing any extant items
+    ///
+    /// # Arguments
+    /// * `items` - Vector of items
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_cb_items<'c, I>(&self, items: Vec<I>)
+    where
+        I: AsRef<str>,
+    {
+        self.inner().set_cb_items(items);
+    }
+
+    /// Install a provider which supplies the candidates offered by the
+    /// combobox, in place of the items set via `set_cb_items`. The provider's
+    /// candidates are loaded on a worker thread, displaying the loading
+    /// indicator in the meantime, and are then fuzzy matched as the user
+    /// types and validate the keys of the items added. The provider is
+    /// never queried on the gui thread.
+    ///
+    /// # Arguments
+    /// * `provider` - The CandidateProvider, eg DirectoryCandidates
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_candidate_provider<P>(&self, provider: P)
+    where
+        P: CandidateProvider + 'static,
+    {
+        self.inner().set_candidate_provider(Arc::new(provider));
+    }
+
+    /// Remove the candidate provider, reverting to the items set via
+    /// `set_cb_items`
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_candidate_provider(&self) {
+        self.inner().clear_candidate_provider();
+    }
+
+    /// Replace the combobox items with the candidates of the provider. The
+    /// candidates are loaded on a worker thread, and stream into the combobox
+    /// in batches, so that a slow provider does not block the gui. A loading
+    /// indicator is displayed in the meantime. Starting a new load, or
+    /// setting or removing the items, cancels the load in progress.
+    ///
+    /// # Arguments
+    /// * `provider` - The CandidateProvider, eg DirectoryCandidates
+    ///
+    /// # Returns
+    /// * None
+    pub fn load_cb_items<P>(&self, provider: P)
+    where
+        P: CandidateProvider + 'static,
+    {
+        self.inner().load_cb_items(Arc::new(provider));
+    }
+
+    /// Cancel the load of combobox items in progress, retaining the items
+    /// which have already arrived
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn cancel_cb_items_load(&self) {
+        self.inner().cancel_cb_items_load();
+    }
+
+    /// Determine whether combobox items are being loaded in the background
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_loading_cb_items(&self) -> bool {
+        self.inner().is_loading_cb_items()
+    }
+
+    #[allow(dead_code)]
+    /// Remove all items from the combobox
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns None
+    pub fn remove_cb_items(&self) {
+        self.inner().remove_cb_items();
+    }
+
+    /// Change the max number of items displayed in the combobox's dropdown
+    /// list
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of visible items in the comobobox's dropdown
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_cb_max_visible_items(&self, max: i32) {
+        self.inner().set_cb_max_visible_items(max);
+    }
+
+    /// Given a path as a &str to a stylesheet, apply it to the components.
+    ///
+    /// # Arguments
+    /// * `sheet` - Path to the qss stylesheet
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_stylesheet(&self, sheet: &str) {
+        self.inner().set_stylesheet(sheet);
+    }
+
+    /// Set the keys which may appear more than once, as long as each
+    /// appearance has different text (eg withs of a package carrying
+    /// different version constraints)
+    ///
+    /// # Arguments
+    /// * `keys` - Vector of keys, as &str or String
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_allowed_duplicates<I: Into<String>>(&self, keys: Vec<I>) {
+        self.inner()
+            .set_allowed_duplicates(keys.into_iter().map(Into::into).collect());
+    }
+
+    /// Set the component to add mode
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_add_mode(&self) {
+        self.inner().set_add_mode();
+    }
+
+    #[allow(dead_code)]
+    /// Set the component to find mode
+    ///
+    /// # Arguments
+    /// * None
     ///
     /// # Returns
     /// * None
     pub fn set_find_mode(&self) {
         self.inner().set_find_mode();
     }
+
+    /// Set the component to filter mode. In filter mode, the items which
+    /// do not match the text entered by the user are hidden. The text is
+    /// matched against the text of each item, without the DISABLED_PREFIX.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_filter_mode(&self) {
+        self.inner().set_filter_mode();
+    }
+
+    /// Set the strategy used to match the filter text against the items.
+    /// Defaults to FilterMatch::Substring
+    ///
+    /// # Arguments
+    /// * `filter_match` - The FilterMatch strategy
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_filter_match(&self, filter_match: FilterMatch) {
+        self.inner().set_filter_match(filter_match);
+    }
+
+    /// Retrieve the strategy used to match the filter text against the items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * FilterMatch
+    pub fn filter_match(&self) -> FilterMatch {
+        self.inner().filter_match()
+    }
+
+    /// Hide the items which do not match the supplied text
+    ///
+    /// # Arguments
+    /// * `text` - The filter text. An empty text shows all of the items
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_filter_text(&self, text: &str) {
+        self.inner().set_filter_text(text);
+    }
+
+    /// Show all of the items, removing any filter
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_filter(&self) {
+        self.inner().clear_filter();
+    }
+}
+
+/// The list of withs
+pub type WithsList<'l> = ItemList<'l, WithEntry>;
+
+// Files, dependencies and versions are those of packages, so are only
+// available to lists of withs
+impl<'l> ItemList<'l, WithEntry> {
+    /// Replace the items with those read from a file. The format is
+    /// determined by the file's extension: `.json`, `.yaml` / `.yml`, or
+    /// `.txt` / `.withs` for newline delimited text. As with `set_items`,
+    /// the items become the baseline against which `is_dirty` is measured.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Ok, or a PersistError if the file may not be read or is malformed
+    pub fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<(), PersistError> {
+        self.inner().import_file(path.as_ref())
+    }
+
+    /// Write the items to a file, in the format determined by the file's
+    /// extension. When writing text, the comments and blank lines of the last
+    /// imported text file are preserved. Exporting does not affect `is_dirty`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * Ok, or a PersistError
+    pub fn export_file<P: AsRef<Path>>(&self, path: P) -> Result<(), PersistError> {
+        self.inner().export_file(path.as_ref())
+    }
+
+    /// Retrieve the items, expanded to include the dependencies reported by
+    /// the dependency resolver. Each missing dependency precedes the first
+    /// item which requires it.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items, which are the same as `items()` without a resolver
+    pub fn resolved_items(&self) -> Vec<String> {
+        self.inner().resolved_items()
+    }
+
+    /// Retrieve the items which are dependencies of other items in the list.
+    /// These are displayed in italics.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of items, in list order
+    pub fn implied_items(&self) -> Vec<String> {
+        self.inner().implied_items()
+    }
+
+    /// Retrieve the dependencies of an item which are absent from the list
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * Vector of package names
+    pub fn missing_dependencies(&self, item: &str) -> Vec<String> {
+        self.inner().missing_dependencies(item)
+    }
+
+    /// Install a resolver reporting the dependencies of packages. When the
+    /// user adds an item whose dependencies are missing, they are offered
+    /// the chance to add them as well.
+    ///
+    /// # Arguments
+    /// * `resolver` - The DependencyResolver, eg MapResolver
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_dependency_resolver<R>(&self, resolver: R)
+    where
+        R: DependencyResolver + 'static,
+    {
+        self.inner().set_dependency_resolver(Box::new(resolver));
+    }
+
+    /// Remove the dependency resolver
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_dependency_resolver(&self) {
+        self.inner().clear_dependency_resolver();
+    }
+
+    /// Set the versions available for a package. Once the user has entered
+    /// the package name in the combobox, followed by a dash or a comparison
+    /// operator (eg `houdini-` or `maya>=`), the versions are offered as
+    /// completions.
+    ///
+    /// # Arguments
+    /// * `package` - The name of the package
+    /// * `versions` - Vector of versions, as &str or String
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_package_versions<I>(&self, package: &str, versions: Vec<I>)
+    where
+        I: AsRef<str>,
+    {
+        self.inner().set_package_versions(package, versions);
+    }
+
+    /// Remove the versions of all packages
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear_package_versions(&self) {
+        self.inner().clear_package_versions();
+    }
 }
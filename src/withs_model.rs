@@ -0,0 +1,1245 @@
+//! Headless model of a list of items.
+//!
+//! The ItemModel owns the ordered list of items and enforces the editing
+//! rules (no duplicate keys, valid items). It is generic over the ListItem
+//! which it holds, and has no dependency on Qt, which allows it to be reused
+//! by command line and server tools, while the ItemList drives its
+//! QStandardItemModel from it. The WithsModel is the ItemModel of withs,
+//! whose key is their package name.
+//!
+//! Items are looked up by key through an index of the rows each key
+//! occupies, so that lookups, and the duplicate checks made by each
+//! insertion, remain constant time for lists of thousands of items. Edits
+//! renumber only the rows they shift, rather than rebuilding the index.
+//!
+//! Items may be locked in place, as they are when mandated by the show's
+//! configuration. Locked items occupy the top of the model, in the order
+//! they were locked, and may be neither removed, moved nor disabled.
+use crate::list_item::ListItem;
+use crate::with_entry::WithEntry;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// Errors returned when an edit would violate the invariants of the ItemModel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemModelError {
+    /// The item's key is already a member of the list
+    Duplicate(String),
+    /// The item could not be parsed
+    InvalidName(String),
+    /// The item is not a member of the list
+    NotFound(String),
+    /// The row is outside of the bounds of the list
+    OutOfRange(usize),
+    /// The item is locked in place
+    Locked(String),
+}
+
+impl fmt::Display for ItemModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate(name) => write!(f, "'{}' is already in the list", name),
+            Self::InvalidName(name) => write!(f, "'{}' is not a valid item", name),
+            Self::NotFound(name) => write!(f, "'{}' is not in the list", name),
+            Self::OutOfRange(row) => write!(f, "row {} is out of range", row),
+            Self::Locked(name) => write!(f, "'{}' is locked", name),
+        }
+    }
+}
+
+impl Error for ItemModelError {}
+
+/// The errors of the WithsModel
+pub type WithsModelError = ItemModelError;
+
+/// Ordered list of items, free of duplicate keys, other than those
+/// explicitly allowed to appear more than once. The model also tracks a
+/// baseline snapshot of the items (typically the last saved state), in order
+/// to determine whether it has been edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemModel<T> {
+    items: Vec<T>,
+    baseline: Vec<T>,
+    allowed_duplicates: BTreeSet<String>,
+    // the rows occupied by each key, in ascending order
+    index: HashMap<String, Vec<usize>>,
+    // the locked items, which occupy the top rows, and the source of the lock
+    locked: Vec<T>,
+    lock_source: String,
+}
+
+/// The ItemModel of withs
+pub type WithsModel = ItemModel<WithEntry>;
+
+impl<T> Default for ItemModel<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            baseline: Vec::new(),
+            allowed_duplicates: BTreeSet::new(),
+            index: HashMap::new(),
+            locked: Vec::new(),
+            lock_source: String::new(),
+        }
+    }
+}
+
+impl<T: ListItem> ItemModel<T> {
+    /// New up an empty ItemModel
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * An empty ItemModel instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New up an ItemModel from a vector of items, which also serve
+    /// as the baseline.
+    ///
+    /// # Arguments
+    /// * `items` - a Vector of &str or String
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemModel, or the first error encountered
+    pub fn from_items<I>(items: Vec<I>) -> Result<Self, ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        let mut model = Self::new();
+        model.set_items(items)?;
+        model.mark_clean();
+        Ok(model)
+    }
+
+    /// New up an ItemModel from a vector of items, as per `from_items`,
+    /// allowing the supplied keys to appear more than once
+    ///
+    /// # Arguments
+    /// * `items` - a Vector of &str or String
+    /// * `allowed_duplicates` - Vector of keys, as &str or String
+    ///
+    /// # Returns
+    /// * Ok wrapping the ItemModel, or the first error encountered
+    pub fn from_items_with<I, P>(
+        items: Vec<I>,
+        allowed_duplicates: Vec<P>,
+    ) -> Result<Self, ItemModelError>
+    where
+        I: AsRef<str>,
+        P: Into<String>,
+    {
+        let mut model = Self::new();
+        model.set_allowed_duplicates(allowed_duplicates);
+        model.set_items(items)?;
+        model.mark_clean();
+        Ok(model)
+    }
+
+    /// Determine whether the supplied name is a valid item, as parsed by
+    /// its ListItem implementation (eg a with is a package name, optionally
+    /// followed by a version constraint).
+    ///
+    /// # Arguments
+    /// * `name` - The name of the item
+    ///
+    /// # Returns
+    /// * bool indicating whether the name is valid
+    pub fn is_valid_name(name: &str) -> bool {
+        T::parse_item(name).is_ok()
+    }
+
+    /// Retrieve the enabled items as a vector of Strings, formatted as per
+    /// their Display implementation (eg withs with their version
+    /// constraints)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub fn items(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter(|entry| entry.is_enabled())
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Retrieve all of the items, enabled or not, as a vector of Strings.
+    /// Disabled withs carry the DISABLED_PREFIX, so that they may be
+    /// restored via `set_items`.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub fn all_items(&self) -> Vec<String> {
+        self.items.iter().map(ToString::to_string).collect()
+    }
+
+    /// Retrieve the entries as a slice of items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * slice of items
+    pub fn entries(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Retrieve the number of items in the model
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Determine whether the model is empty
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the model holds no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Retrieve the entry at the supplied row
+    ///
+    /// # Arguments
+    /// * `row` - The row of the item
+    ///
+    /// # Returns
+    /// * Some(&item) if the row is in range, None otherwise
+    pub fn get(&self, row: usize) -> Option<&T> {
+        self.items.get(row)
+    }
+
+    /// Determine whether the item's key is a member of the model
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * bool indicating whether the key is present
+    pub fn contains(&self, item: &str) -> bool {
+        self.position(item).is_some()
+    }
+
+    /// Retrieve the row of the supplied item's key. Items are compared by
+    /// key (eg the package of a with, ignoring its version constraint),
+    /// unless the key appears more than once, in which case the item with
+    /// the same text is preferred, whether or not either is disabled.
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * Some(row) if the key is present, None otherwise
+    pub fn position(&self, item: &str) -> Option<usize> {
+        let (name, text) = Self::identify(item);
+        let rows = self.index.get(&name)?;
+        if rows.len() > 1 {
+            if let Some(row) = rows
+                .iter()
+                .find(|row| self.items[**row].item_text() == text)
+            {
+                return Some(*row);
+            }
+        }
+        rows.first().copied()
+    }
+
+    /// Set the keys which may appear more than once, as long as each
+    /// appearance carries different text (eg a different version constraint)
+    ///
+    /// # Arguments
+    /// * `keys` - Vector of keys, as &str or String
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_allowed_duplicates<I>(&mut self, keys: Vec<I>)
+    where
+        I: Into<String>,
+    {
+        self.allowed_duplicates = keys.into_iter().map(Into::into).collect();
+    }
+
+    /// Retrieve the keys which may appear more than once, in order
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec<String>
+    pub fn allowed_duplicates(&self) -> Vec<String> {
+        self.allowed_duplicates.iter().cloned().collect()
+    }
+
+    /// Determine whether the key may appear more than once
+    ///
+    /// # Arguments
+    /// * `key` - The key
+    ///
+    /// # Returns
+    /// * bool
+    pub fn allows_duplicates(&self, key: &str) -> bool {
+        self.allowed_duplicates.contains(key)
+    }
+
+    /// Determine whether the supplied item would be rejected as a duplicate
+    /// of an item in the model. An item whose key may appear more than once
+    /// is only a duplicate of an identical item.
+    ///
+    /// # Arguments
+    /// * `item` - The item, or its key
+    ///
+    /// # Returns
+    /// * bool indicating whether adding the item would fail as a Duplicate
+    pub fn is_duplicate(&self, item: &str) -> bool {
+        match T::parse_item(item) {
+            Ok(entry) => matches!(self.validate(&entry), Err(ItemModelError::Duplicate(_))),
+            Err(_) => false,
+        }
+    }
+
+    /// Lock the supplied items in place, replacing any previously locked.
+    /// The items are moved, or added if absent, to the top of the model, in
+    /// the supplied order, and the rest of the items follow in their current
+    /// order. The baseline is likewise updated, as locked items are mandated
+    /// rather than edited. The model is left untouched if any of the items
+    /// is invalid or duplicated.
+    ///
+    /// # Arguments
+    /// * `items` - a Vector of &str or String
+    /// * `source` - Describes what locked the items (eg `show config`)
+    ///
+    /// # Returns
+    /// * Ok, or the first error encountered
+    pub fn set_locked_items<I>(&mut self, items: Vec<I>, source: &str) -> Result<(), ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        let mut locked = self.empty_clone();
+        for item in items {
+            let mut entry = T::parse_item(item.as_ref())
+                .map_err(|_| ItemModelError::InvalidName(item.as_ref().to_string()))?;
+            // locked items are mandated, and so may not be disabled
+            entry.set_enabled(true);
+            locked.insert_entry(locked.len(), entry)?;
+        }
+        self.locked = locked.items;
+        self.lock_source = source.to_string();
+        let (items, baseline) = (self.items.clone(), self.baseline.clone());
+        self.items = self.pin_locked(items);
+        self.baseline = self.pin_locked(baseline);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Retrieve the locked items, in the order they occupy the top of the
+    /// model
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * slice of items
+    pub fn locked_items(&self) -> &[T] {
+        &self.locked
+    }
+
+    /// Retrieve the description of what locked the items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some(source), or None if no items are locked
+    pub fn lock_source(&self) -> Option<&str> {
+        if self.locked.is_empty() {
+            return None;
+        }
+        Some(&self.lock_source)
+    }
+
+    /// Determine whether the item at the supplied row is locked
+    ///
+    /// # Arguments
+    /// * `row` - The row of the item
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_locked(&self, row: usize) -> bool {
+        row < self.locked.len()
+    }
+
+    /// Enable or disable the item at the supplied row. Disabled items keep
+    /// their place in the model, but are left out of `items`.
+    ///
+    /// # Arguments
+    /// * `row` - The row of the item
+    /// * `enabled` - Whether the item should be enabled
+    ///
+    /// # Returns
+    /// * Ok wrapping whether the item changed, or an error if the row is
+    ///   out of range or locked
+    pub fn set_enabled(&mut self, row: usize, enabled: bool) -> Result<bool, ItemModelError> {
+        self.check_unlocked(&[row])?;
+        let entry = self
+            .items
+            .get_mut(row)
+            .ok_or(ItemModelError::OutOfRange(row))?;
+        let changed = entry.is_enabled() != enabled;
+        entry.set_enabled(enabled);
+        Ok(changed && entry.is_enabled() == enabled)
+    }
+
+    /// Append an item to the end of the model
+    ///
+    /// # Arguments
+    /// * `item` - The name of the item
+    ///
+    /// # Returns
+    /// * Ok wrapping the row of the new item, or an error if the item is
+    ///   invalid or already present
+    pub fn add(&mut self, item: &str) -> Result<usize, ItemModelError> {
+        self.insert(self.items.len(), item)
+    }
+
+    /// Insert an item at the supplied row. Rows among the locked items
+    /// insert the item beneath them.
+    ///
+    /// # Arguments
+    /// * `row` - The row to insert the item at. May be equal to the length
+    ///   of the model, in which case the item is appended
+    /// * `item` - The name of the item
+    ///
+    /// # Returns
+    /// * Ok wrapping the row of the new item, or an error
+    pub fn insert(&mut self, row: usize, item: &str) -> Result<usize, ItemModelError> {
+        let entry =
+            T::parse_item(item).map_err(|_| ItemModelError::InvalidName(item.to_string()))?;
+        self.insert_entry(row, entry)
+    }
+
+    /// Insert a parsed item at the supplied row
+    ///
+    /// # Arguments
+    /// * `row` - The row to insert the entry at
+    /// * `entry` - The item
+    ///
+    /// # Returns
+    /// * Ok wrapping the row of the new entry, or an error
+    pub fn insert_entry(&mut self, row: usize, entry: T) -> Result<usize, ItemModelError> {
+        self.validate(&entry)?;
+        if row > self.items.len() {
+            return Err(ItemModelError::OutOfRange(row));
+        }
+        let row = row.max(self.locked.len());
+        let len = self.items.len();
+        self.unindex(row..len);
+        self.items.insert(row, entry);
+        self.index_rows(row..len + 1);
+        Ok(row)
+    }
+
+    /// Insert several items as a block, starting at the supplied row. Rows
+    /// among the locked items insert the block beneath them. The model is
+    /// left untouched if any of the items is invalid or duplicated.
+    ///
+    /// # Arguments
+    /// * `row` - The row to insert the first item at
+    /// * `items` - a slice of &str or String
+    ///
+    /// # Returns
+    /// * Ok wrapping the rows of the new items, or the first error
+    ///   encountered
+    pub fn insert_items<I>(
+        &mut self,
+        row: usize,
+        items: &[I],
+    ) -> Result<Range<usize>, ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        if row > self.items.len() {
+            return Err(ItemModelError::OutOfRange(row));
+        }
+        // the block is placed once, so that each item follows the last
+        let row = row.max(self.locked.len());
+        let mut model = self.clone();
+        for (offset, item) in items.iter().enumerate() {
+            model.insert(row + offset, item.as_ref())?;
+        }
+        *self = model;
+        Ok(row..row + items.len())
+    }
+
+    /// Remove the supplied item from the model
+    ///
+    /// # Arguments
+    /// * `item` - The name of the item
+    ///
+    /// # Returns
+    /// * Ok wrapping the row the item occupied, or an error if the item is
+    ///   absent or locked
+    pub fn remove(&mut self, item: &str) -> Result<usize, ItemModelError> {
+        let row = self
+            .position(item)
+            .ok_or_else(|| ItemModelError::NotFound(item.to_string()))?;
+        self.remove_at(row)?;
+        Ok(row)
+    }
+
+    /// Remove the item at the supplied row
+    ///
+    /// # Arguments
+    /// * `row` - The row of the item to remove
+    ///
+    /// # Returns
+    /// * Ok wrapping the removed entry, or an error if the row is out of
+    ///   range or locked
+    pub fn remove_at(&mut self, row: usize) -> Result<T, ItemModelError> {
+        self.check_unlocked(&[row])?;
+        let len = self.items.len();
+        self.unindex(row..len);
+        let entry = self.items.remove(row);
+        self.index_rows(row..len - 1);
+        Ok(entry)
+    }
+
+    /// Remove the items at the supplied rows. The rows may be supplied in
+    /// any order and may contain duplicates. The model is left untouched if
+    /// any of the rows is out of range or locked.
+    ///
+    /// # Arguments
+    /// * `rows` - The rows of the items to remove
+    ///
+    /// # Returns
+    /// * Ok wrapping the removed rows and entries, in descending row order,
+    ///   or an error
+    pub fn remove_rows(&mut self, rows: &[usize]) -> Result<Vec<(usize, T)>, ItemModelError> {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+        self.check_unlocked(&rows)?;
+        let first = match rows.first() {
+            Some(first) => *first,
+            None => return Ok(Vec::new()),
+        };
+        // the rows following the first removal are partitioned in a single
+        // pass, rather than removed one at a time, which would shift the
+        // remainder for each removal
+        let len = self.items.len();
+        self.unindex(first..len);
+        let mut removed = Vec::with_capacity(rows.len());
+        let mut kept = Vec::with_capacity(len - first - rows.len());
+        for (row, entry) in self.items.drain(first..).enumerate() {
+            let row = first + row;
+            if rows.binary_search(&row).is_ok() {
+                removed.push((row, entry));
+            } else {
+                kept.push(entry);
+            }
+        }
+        self.items.append(&mut kept);
+        self.index_rows(first..self.items.len());
+        removed.reverse();
+        Ok(removed)
+    }
+
+    /// Move the item at row `from` so that it ends up at row `to`
+    ///
+    /// # Arguments
+    /// * `from` - The current row of the item
+    /// * `to` - The row the item should occupy after the move
+    ///
+    /// # Returns
+    /// * Ok, or an error if either row is out of bounds or locked
+    pub fn move_item(&mut self, from: usize, to: usize) -> Result<(), ItemModelError> {
+        self.check_unlocked(&[from, to])?;
+        let affected = from.min(to)..from.max(to) + 1;
+        self.unindex(affected.clone());
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        self.index_rows(affected);
+        Ok(())
+    }
+
+    /// Move the items at the supplied rows, as a contiguous block in their
+    /// current order, so that the first of them ends up at row `to`. The
+    /// rows may be supplied in any order and may contain duplicates. `to`
+    /// is clamped so that the block remains within the model, beneath any
+    /// locked items.
+    ///
+    /// # Arguments
+    /// * `rows` - The current rows of the items
+    /// * `to` - The row the first item should occupy after the move
+    ///
+    /// # Returns
+    /// * Ok wrapping the range of rows occupied by the items after the move,
+    ///   or an error if any of the rows is out of bounds or locked
+    pub fn move_rows(&mut self, rows: &[usize], to: usize) -> Result<Range<usize>, ItemModelError> {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+        self.check_unlocked(&rows)?;
+        let count = rows.len();
+        let to = to.min(self.items.len() - count).max(self.locked.len());
+        let (first, last) = match (rows.first(), rows.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Ok(to..to),
+        };
+        // only the rows between the first row affected and the last are
+        // rearranged and renumbered
+        let affected = first.min(to)..(last + 1).max(to + count);
+        self.unindex(affected.clone());
+        let mut moved = Vec::with_capacity(count);
+        let mut kept = Vec::with_capacity(affected.len() - count);
+        for (row, entry) in self.items.drain(affected.clone()).enumerate() {
+            if rows.binary_search(&(affected.start + row)).is_ok() {
+                moved.push(entry);
+            } else {
+                kept.push(entry);
+            }
+        }
+        let at = to - affected.start;
+        kept.splice(at..at, moved);
+        self.items.splice(affected.start..affected.start, kept);
+        self.index_rows(affected);
+        Ok(to..to + count)
+    }
+
+    /// Determine whether moving the items at the supplied rows, as per
+    /// `move_rows`, would change the order of the items. A move changes
+    /// nothing when the rows already form a contiguous block at the row
+    /// they would be moved to, once clamped.
+    ///
+    /// # Arguments
+    /// * `rows` - The current rows of the items
+    /// * `to` - The row the first item should occupy after the move
+    ///
+    /// # Returns
+    /// * bool, which is false if any of the rows is out of bounds or locked
+    pub fn would_move_rows(&self, rows: &[usize], to: usize) -> bool {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+        let (first, last) = match (rows.first(), rows.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return false,
+        };
+        if self.check_unlocked(&rows).is_err() {
+            return false;
+        }
+        let to = to.min(self.items.len() - rows.len()).max(self.locked.len());
+        last - first + 1 != rows.len() || first != to
+    }
+
+    /// Sets the contents to items, removing any pre-existing items. The
+    /// locked items remain at the top of the model, in their locked order,
+    /// whether or not they are among the items. The model is left untouched
+    /// if any of the items is invalid.
+    ///
+    /// # Arguments
+    /// * `items` - a Vector of &str or String
+    ///
+    /// # Returns
+    /// * Ok, or the first error encountered
+    pub fn set_items<I>(&mut self, items: Vec<I>) -> Result<(), ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        let mut new_model = self.empty_clone();
+        for item in items {
+            new_model.add(item.as_ref())?;
+        }
+        self.items = self.pin_locked(new_model.items);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Reorder the model to match the supplied items, which must be a
+    /// permutation of the current items, beginning with the locked items.
+    ///
+    /// # Arguments
+    /// * `items` - a Vector of &str or String in the new order
+    ///
+    /// # Returns
+    /// * Ok, or an error if items is not a permutation of the model's items,
+    ///   or moves a locked item
+    pub fn reorder<I>(&mut self, items: Vec<I>) -> Result<(), ItemModelError>
+    where
+        I: AsRef<str>,
+    {
+        let mut reordered = self.empty_clone();
+        reordered.set_items(items)?;
+        if reordered.len() != self.len() {
+            return Err(ItemModelError::OutOfRange(reordered.len()));
+        }
+        if let Some(missing) = reordered.items.iter().find(|x| !self.contains(x.key())) {
+            return Err(ItemModelError::NotFound(missing.to_string()));
+        }
+        if let Some((locked, _)) = self
+            .locked
+            .iter()
+            .zip(&reordered.items)
+            .find(|(locked, entry)| locked.key() != entry.key())
+        {
+            return Err(ItemModelError::Locked(locked.to_string()));
+        }
+        self.items = reordered.items;
+        self.index = reordered.index;
+        Ok(())
+    }
+
+    /// Remove all of the items from the model, other than those locked
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn clear(&mut self) {
+        self.items = self.locked.clone();
+        self.reindex();
+    }
+
+    /// Retrieve the baseline snapshot of the items
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * slice of items
+    pub fn baseline(&self) -> &[T] {
+        &self.baseline
+    }
+
+    /// Snapshot the current items as the baseline, marking the model as clean
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn mark_clean(&mut self) {
+        self.baseline = self.items.clone();
+    }
+
+    /// Determine whether the items differ from the baseline
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool indicating whether the model has been edited since the baseline was taken
+    pub fn is_dirty(&self) -> bool {
+        self.items != self.baseline
+    }
+
+    /// Restore the items from the baseline
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn revert(&mut self) {
+        self.items = self.pin_locked(self.baseline.clone());
+        self.reindex();
+    }
+
+    // retrieve the key of an item, along with its text without the
+    // disabled prefix, falling back on the item itself if it does not
+    // parse
+    fn identify(item: &str) -> (String, String) {
+        match T::parse_item(item) {
+            Ok(entry) => (entry.key().to_string(), entry.item_text()),
+            Err(_) => (item.to_string(), item.to_string()),
+        }
+    }
+
+    // determine whether two items are the same appearance of a key which
+    // may appear more than once, whether or not either is enabled
+    fn is_same_item(&self, x: &T, entry: &T) -> bool {
+        x.key() == entry.key()
+            && (!self.allows_duplicates(entry.key()) || x.item_text() == entry.item_text())
+    }
+
+    // verify that the entry may be added to the model
+    fn validate(&self, entry: &T) -> Result<(), ItemModelError> {
+        let duplicate = match self.index.get(entry.key()) {
+            Some(rows) => rows
+                .iter()
+                .any(|row| self.is_same_item(&self.items[*row], entry)),
+            None => false,
+        };
+        if duplicate {
+            return Err(ItemModelError::Duplicate(entry.to_string()));
+        }
+        Ok(())
+    }
+
+    // verify that none of the rows is out of range or locked
+    fn check_unlocked(&self, rows: &[usize]) -> Result<(), ItemModelError> {
+        for row in rows {
+            if *row >= self.items.len() {
+                return Err(ItemModelError::OutOfRange(*row));
+            }
+            if self.is_locked(*row) {
+                return Err(ItemModelError::Locked(self.items[*row].to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    // place the locked items at the top of the supplied entries, dropping
+    // any other appearance of them
+    fn pin_locked(&self, entries: Vec<T>) -> Vec<T> {
+        if self.locked.is_empty() {
+            return entries;
+        }
+        let is_locked = |entry: &T| {
+            self.locked
+                .iter()
+                .any(|locked| self.is_same_item(locked, entry))
+        };
+        let mut pinned = self.locked.clone();
+        pinned.extend(entries.into_iter().filter(|entry| !is_locked(entry)));
+        pinned
+    }
+
+    // remove the supplied rows from the index, prior to an edit which
+    // shifts them
+    fn unindex(&mut self, range: Range<usize>) {
+        for row in range {
+            let name = self.items[row].key();
+            let now_empty = match self.index.get_mut(name) {
+                Some(rows) => {
+                    if let Ok(idx) = rows.binary_search(&row) {
+                        rows.remove(idx);
+                    }
+                    rows.is_empty()
+                }
+                None => false,
+            };
+            if now_empty {
+                self.index.remove(name);
+            }
+        }
+    }
+
+    // add the supplied rows to the index, following an edit which shifted
+    // them
+    fn index_rows(&mut self, range: Range<usize>) {
+        for row in range {
+            let name = self.items[row].key();
+            match self.index.get_mut(name) {
+                Some(rows) => {
+                    let idx = rows.binary_search(&row).unwrap_or_else(|idx| idx);
+                    rows.insert(idx, row);
+                }
+                None => {
+                    self.index.insert(name.to_string(), vec![row]);
+                }
+            }
+        }
+    }
+
+    // rebuild the index of the rows occupied by each key
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (row, entry) in self.items.iter().enumerate() {
+            self.index
+                .entry(entry.key().to_string())
+                .or_default()
+                .push(row);
+        }
+    }
+
+    // an empty model sharing the allowed duplicates
+    fn empty_clone(&self) -> Self {
+        Self {
+            allowed_duplicates: self.allowed_duplicates.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(items: &[&str]) -> WithsModel {
+        WithsModel::from_items(items.to_vec()).expect("valid items")
+    }
+
+    // a tag, which may hold no whitespace, and may not be disabled
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Tag(String);
+
+    impl fmt::Display for Tag {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl ListItem for Tag {
+        type Error = String;
+
+        fn parse_item(text: &str) -> Result<Self, Self::Error> {
+            if text.is_empty() || text.contains(char::is_whitespace) {
+                return Err(format!("'{}' is not a valid tag", text));
+            }
+            Ok(Tag(text.to_string()))
+        }
+
+        fn key(&self) -> &str {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn insert_places_items_at_the_supplied_row() {
+        let mut model = model(&["houdini", "maya"]);
+        assert_eq!(model.insert(1, "nuke-12.0"), Ok(1));
+        assert_eq!(model.add("katana"), Ok(3));
+        assert_eq!(model.insert(0, "mari"), Ok(0));
+        assert_eq!(
+            model.items(),
+            vec!["mari", "houdini", "nuke-12.0", "maya", "katana"]
+        );
+        assert_eq!(model.position("nuke"), Some(2));
+        assert_eq!(model.position("katana"), Some(4));
+        assert_eq!(model.insert(6, "vray"), Err(ItemModelError::OutOfRange(6)));
+    }
+
+    #[test]
+    fn insert_rejects_invalid_names() {
+        let mut model = model(&["houdini"]);
+        assert_eq!(
+            model.add("maya >= 2020"),
+            Err(ItemModelError::InvalidName("maya >= 2020".to_string()))
+        );
+        assert_eq!(
+            model.add(""),
+            Err(ItemModelError::InvalidName("".to_string()))
+        );
+        assert_eq!(model.items(), vec!["houdini"]);
+    }
+
+    #[test]
+    fn duplicate_packages_are_rejected() {
+        let mut model = model(&["houdini-18.0", "maya"]);
+        assert_eq!(
+            model.add("houdini-18.5"),
+            Err(ItemModelError::Duplicate("houdini-18.5".to_string()))
+        );
+        assert_eq!(
+            model.insert(0, "maya"),
+            Err(ItemModelError::Duplicate("maya".to_string()))
+        );
+        assert_eq!(
+            WithsModel::from_items(vec!["nuke", "nuke-12.0"]),
+            Err(ItemModelError::Duplicate("nuke-12.0".to_string()))
+        );
+        assert_eq!(model.items(), vec!["houdini-18.0", "maya"]);
+    }
+
+    #[test]
+    fn allowed_duplicates_require_distinct_versions() {
+        let mut model = WithsModel::new();
+        model.set_allowed_duplicates(vec!["maya"]);
+        model.set_items(vec!["maya-2019", "maya-2020"]).unwrap();
+        assert_eq!(
+            model.add("maya-2020"),
+            Err(ItemModelError::Duplicate("maya-2020".to_string()))
+        );
+        assert_eq!(model.position("maya-2020"), Some(1));
+        assert_eq!(model.position("maya"), Some(0));
+    }
+
+    #[test]
+    fn duplicates_are_judged_by_the_allowed_duplicates() {
+        let mut model = model(&["nuke-12.0", "maya-2019"]);
+        model.set_allowed_duplicates(vec!["maya"]);
+        assert!(model.is_duplicate("nuke-12.0"));
+        assert!(model.is_duplicate("nuke-13.0"));
+        assert!(model.is_duplicate("maya-2019"));
+        assert!(!model.is_duplicate("maya-2020"));
+        assert!(!model.is_duplicate("houdini"));
+        assert!(!model.is_duplicate("not valid"));
+        model.add("maya-2020").unwrap();
+        assert!(model.is_duplicate("maya-2020"));
+    }
+
+    #[test]
+    fn insert_items_places_the_block_beneath_the_locked_items() {
+        let mut model = model(&["nuke"]);
+        model
+            .set_locked_items(vec!["houdini", "maya"], "show config")
+            .unwrap();
+        assert_eq!(model.insert_items(0, &["katana", "mari", "gcc"]), Ok(2..5));
+        assert_eq!(
+            model.items(),
+            vec!["houdini", "maya", "katana", "mari", "gcc", "nuke"]
+        );
+        assert_eq!(model.position("gcc"), Some(4));
+        assert_eq!(
+            model.insert_items(6, &["make", "maya"]),
+            Err(ItemModelError::Duplicate("maya".to_string()))
+        );
+        assert_eq!(model.len(), 6);
+        assert_eq!(
+            model.insert_items(7, &["make"]),
+            Err(ItemModelError::OutOfRange(7))
+        );
+    }
+
+    #[test]
+    fn allowed_duplicates_are_found_whether_or_not_disabled() {
+        let mut model = WithsModel::new();
+        model.set_allowed_duplicates(vec!["maya"]);
+        model.set_items(vec!["maya-2019", "#~maya-2020"]).unwrap();
+        assert_eq!(model.position("maya-2020"), Some(1));
+        assert_eq!(model.position("#~maya-2020"), Some(1));
+        assert_eq!(model.position("#~maya-2019"), Some(0));
+        assert!(model.contains("maya-2020"));
+        assert_eq!(model.remove("maya-2020"), Ok(1));
+        assert_eq!(model.all_items(), vec!["maya-2019"]);
+    }
+
+    #[test]
+    fn saved_items_keep_their_disabled_withs() {
+        let mut model = model(&["maya-2020", "nuke", "houdini"]);
+        assert_eq!(model.set_enabled(1, false), Ok(true));
+        // the items handed to the save handler are all of the items
+        let saved = model.all_items();
+        assert_eq!(saved, vec!["maya-2020", "#~nuke", "houdini"]);
+        assert_eq!(model.items(), vec!["maya-2020", "houdini"]);
+        let restored = WithsModel::from_items(saved).expect("valid withs");
+        assert_eq!(restored.all_items(), model.all_items());
+        assert_eq!(restored.get(1).map(ListItem::is_enabled), Some(false));
+    }
+
+    #[test]
+    fn set_items_leaves_the_model_untouched_on_error() {
+        let mut model = model(&["houdini"]);
+        assert!(model.set_items(vec!["maya", "maya"]).is_err());
+        assert_eq!(model.items(), vec!["houdini"]);
+    }
+
+    #[test]
+    fn remove_by_name_and_row() {
+        let mut model = model(&["houdini", "maya-2020", "nuke"]);
+        assert_eq!(model.remove("maya"), Ok(1));
+        assert_eq!(
+            model.remove("maya"),
+            Err(ItemModelError::NotFound("maya".to_string()))
+        );
+        assert_eq!(
+            model.remove_at(1).map(|entry| entry.name),
+            Ok("nuke".to_string())
+        );
+        assert_eq!(model.remove_at(1), Err(ItemModelError::OutOfRange(1)));
+        assert_eq!(model.items(), vec!["houdini"]);
+        assert!(!model.contains("nuke"));
+    }
+
+    // the removed rows and items, as returned by remove_rows
+    fn removed(removed: Vec<(usize, WithEntry)>) -> Vec<(usize, String)> {
+        removed
+            .into_iter()
+            .map(|(row, entry)| (row, entry.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn remove_rows_removes_non_contiguous_rows() {
+        let mut model = model(&["a", "b", "c", "d", "e", "f"]);
+        let removed = removed(model.remove_rows(&[1, 3, 4]).unwrap());
+        assert_eq!(
+            removed,
+            vec![
+                (4, "e".to_string()),
+                (3, "d".to_string()),
+                (1, "b".to_string())
+            ]
+        );
+        assert_eq!(model.items(), vec!["a", "c", "f"]);
+        assert_eq!(model.position("f"), Some(2));
+    }
+
+    #[test]
+    fn remove_rows_accepts_reversed_and_unsorted_rows() {
+        let mut reversed = model(&["a", "b", "c", "d", "e"]);
+        let mut unsorted = reversed.clone();
+        let removed_reversed = removed(reversed.remove_rows(&[4, 2, 0]).unwrap());
+        let removed_unsorted = removed(unsorted.remove_rows(&[2, 4, 0]).unwrap());
+        assert_eq!(
+            removed_reversed,
+            vec![
+                (4, "e".to_string()),
+                (2, "c".to_string()),
+                (0, "a".to_string())
+            ]
+        );
+        assert_eq!(removed_unsorted, removed_reversed);
+        assert_eq!(reversed.items(), vec!["b", "d"]);
+        assert_eq!(unsorted.items(), vec!["b", "d"]);
+    }
+
+    #[test]
+    fn remove_rows_ignores_duplicate_rows() {
+        let mut model = model(&["a", "b", "c"]);
+        let removed = removed(model.remove_rows(&[1, 1, 2, 1]).unwrap());
+        assert_eq!(removed, vec![(2, "c".to_string()), (1, "b".to_string())]);
+        assert_eq!(model.items(), vec!["a"]);
+    }
+
+    #[test]
+    fn remove_rows_rejects_rows_out_of_range() {
+        let mut model = model(&["a", "b", "c"]);
+        assert_eq!(
+            model.remove_rows(&[0, 3]),
+            Err(ItemModelError::OutOfRange(3))
+        );
+        assert_eq!(model.items(), vec!["a", "b", "c"]);
+        assert_eq!(model.remove_rows(&[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn move_item_shifts_the_items_between() {
+        let mut model = model(&["a", "b", "c", "d"]);
+        model.move_item(0, 2).unwrap();
+        assert_eq!(model.items(), vec!["b", "c", "a", "d"]);
+        model.move_item(3, 0).unwrap();
+        assert_eq!(model.items(), vec!["d", "b", "c", "a"]);
+        assert_eq!(model.position("a"), Some(3));
+        assert_eq!(model.move_item(4, 0), Err(ItemModelError::OutOfRange(4)));
+        assert_eq!(model.move_item(0, 4), Err(ItemModelError::OutOfRange(4)));
+    }
+
+    #[test]
+    fn move_rows_gathers_a_block() {
+        let mut model = model(&["a", "b", "c", "d", "e"]);
+        assert_eq!(model.move_rows(&[4, 1], 0), Ok(0..2));
+        assert_eq!(model.items(), vec!["b", "e", "a", "c", "d"]);
+        // rows past the end move the block to the bottom
+        assert_eq!(model.move_rows(&[0, 2], 10), Ok(3..5));
+        assert_eq!(model.items(), vec!["e", "c", "d", "b", "a"]);
+        assert_eq!(model.position("b"), Some(3));
+        assert_eq!(
+            model.move_rows(&[0, 5], 0),
+            Err(ItemModelError::OutOfRange(5))
+        );
+    }
+
+    #[test]
+    fn index_follows_the_rows_through_edits() {
+        let mut model = WithsModel::new();
+        model.set_allowed_duplicates(vec!["p3"]);
+        // a simple linear congruential generator makes the edits repeatable
+        let mut seed = 7u64;
+        let mut next = |bound: usize| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as usize % bound.max(1)
+        };
+        for step in 0..2_000 {
+            let len = model.len();
+            let item = format!("p{}-{}", next(500), next(3));
+            let _ = match next(7) {
+                0..=2 => model.insert(next(len + 1), &item).map(|_| ()),
+                3 => model.remove_at(next(len)).map(|_| ()),
+                4 => model.move_item(next(len), next(len)),
+                5 => model
+                    .remove_rows(&[next(len), next(len), next(len)])
+                    .map(|_| ()),
+                _ => model
+                    .move_rows(&[next(len), next(len)], next(len + 2))
+                    .map(|_| ()),
+            };
+            let mut rebuilt = model.clone();
+            rebuilt.reindex();
+            assert_eq!(model.index, rebuilt.index, "step {}", step);
+        }
+        assert!(!model.is_empty());
+    }
+
+    #[test]
+    fn moves_which_change_nothing_are_detected() {
+        let mut model = model(&["a", "b", "c", "d"]);
+        model.set_locked_items(vec!["a"], "show config").unwrap();
+        // a block already beneath the locked items cannot move up
+        assert!(!model.would_move_rows(&[1, 2], 0));
+        assert!(!model.would_move_rows(&[1], 1));
+        assert!(model.would_move_rows(&[1, 2], 2));
+        // a scattered selection is gathered, even at the top or bottom
+        assert!(model.would_move_rows(&[1, 3], 0));
+        assert!(model.would_move_rows(&[1, 3], 4));
+        assert!(!model.would_move_rows(&[3, 2], 4));
+        // locked, out of range and empty selections do not move
+        assert!(!model.would_move_rows(&[0], 3));
+        assert!(!model.would_move_rows(&[4], 0));
+        assert!(!model.would_move_rows(&[], 0));
+        for (rows, to) in [(vec![1, 3], 0), (vec![2], 1), (vec![1, 2], 4)] {
+            let mut moved = model.clone();
+            moved.move_rows(&rows, to).unwrap();
+            assert_eq!(
+                model.would_move_rows(&rows, to),
+                moved.all_items() != model.all_items()
+            );
+        }
+    }
+
+    #[test]
+    fn reorder_requires_a_permutation() {
+        let mut model = model(&["a", "b", "c"]);
+        model.reorder(vec!["c", "a", "b"]).unwrap();
+        assert_eq!(model.items(), vec!["c", "a", "b"]);
+        assert!(model.reorder(vec!["c", "a"]).is_err());
+        assert_eq!(
+            model.reorder(vec!["c", "a", "d"]),
+            Err(ItemModelError::NotFound("d".to_string()))
+        );
+        assert_eq!(model.items(), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn edits_are_measured_against_the_baseline() {
+        let mut model = model(&["a", "b"]);
+        assert!(!model.is_dirty());
+        model.add("c").unwrap();
+        assert!(model.is_dirty());
+        model.revert();
+        assert!(!model.is_dirty());
+        assert_eq!(model.items(), vec!["a", "b"]);
+        assert_eq!(model.position("b"), Some(1));
+    }
+
+    #[test]
+    fn item_models_are_ruled_by_their_list_item() {
+        let mut model = ItemModel::<Tag>::from_items(vec!["rust", "qt"]).expect("valid tags");
+        assert_eq!(model.insert(1, "serde"), Ok(1));
+        assert!(model.insert(0, "rust").is_err());
+        assert!(model.insert(0, "two words").is_err());
+        assert_eq!(model.position("qt"), Some(2));
+        // tags ignore requests to disable them
+        assert_eq!(model.set_enabled(0, false), Ok(false));
+        assert_eq!(model.items(), vec!["rust", "serde", "qt"]);
+    }
+}